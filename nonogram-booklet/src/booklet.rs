@@ -0,0 +1,487 @@
+//! Laying out puzzles as PostScript pages
+//!
+//! Each page is split into a grid of equal slots, one puzzle to a slot, and
+//! each puzzle's cells are scaled to fill its slot. The solutions follow on
+//! pages of their own, smaller and without clues.
+
+use nonogram::nonogram_file::{Color, ColorCode, Hint, NonogramFile};
+use nonogram::{Nonogram, Tile};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Solutions are small, so more of them fit on a page.
+const SOLUTIONS_PER_PAGE: usize = 12;
+/// The margin around each page, in points.
+const MARGIN: f64 = 36.0;
+/// The space between slots, in points.
+const GUTTER: f64 = 18.0;
+/// The largest cell, in points, so small puzzles aren't drawn huge.
+const MAX_CELL: f64 = 24.0;
+const MAX_SOLUTION_CELL: f64 = 12.0;
+/// Draw a thick line between every this many rows and columns.
+const GUIDE_EVERY: usize = 5;
+
+/// Helpers for drawing text and lines, defined once at the start.
+const PROLOG: &str = "\
+/F { /Helvetica findfont exch scalefont setfont } bind def
+/L { moveto show } bind def
+/C { moveto dup stringwidth pop 2 div neg 0 rmoveto show } bind def
+/R { moveto dup stringwidth pop neg 0 rmoveto show } bind def
+/S { newpath moveto lineto stroke } bind def
+";
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Paper {
+    A4,
+    Letter,
+}
+
+impl Paper {
+    /// The width and height in points.
+    fn size(self) -> (f64, f64) {
+        match self {
+            Paper::A4 => (595.0, 842.0),
+            Paper::Letter => (612.0, 792.0),
+        }
+    }
+}
+
+pub struct Puzzle {
+    /// Shown above the puzzle and its solution.
+    pub name: String,
+    pub file: NonogramFile,
+    /// The solver's solution, if it found one.
+    pub solution: Option<Nonogram>,
+}
+
+pub struct Booklet {
+    paper: Paper,
+    per_page: usize,
+    title: Option<String>,
+    do_include_solutions: bool,
+}
+
+/// A rectangle on the page, by its top left corner, in points from the
+/// bottom left of the page.
+#[derive(Debug, Copy, Clone)]
+struct Slot {
+    left: f64,
+    top: f64,
+    width: f64,
+    height: f64,
+}
+
+impl Booklet {
+    pub fn new(paper: Paper) -> Self {
+        Booklet {
+            paper,
+            per_page: 1,
+            title: None,
+            do_include_solutions: true,
+        }
+    }
+
+    pub fn with_paper(mut self, paper: Paper) -> Self {
+        self.paper = paper;
+        self
+    }
+
+    /// Put `per_page` puzzles on each page, at least 1.
+    pub fn with_per_page(mut self, per_page: usize) -> Self {
+        self.per_page = per_page.max(1);
+        self
+    }
+
+    /// Print `title` at the top of every page.
+    pub fn with_title(mut self, title: Option<String>) -> Self {
+        self.title = title;
+        self
+    }
+
+    pub fn with_solutions(mut self, do_include_solutions: bool) -> Self {
+        self.do_include_solutions = do_include_solutions;
+        self
+    }
+
+    /// The whole booklet as a PostScript document.
+    pub fn write(&self, puzzles: &[Puzzle]) -> String {
+        let (width, height) = self.paper.size();
+        let header = height - MARGIN / 2.0 - 5.0;
+        let mut pages = Vec::new();
+        for (index, chunk) in puzzles.chunks(self.per_page).enumerate() {
+            let slots = self.slots(self.per_page);
+            let mut page = String::new();
+            for (offset, (puzzle, slot)) in chunk.iter().zip(slots).enumerate() {
+                let number = index * self.per_page + offset + 1;
+                draw_puzzle(&mut page, number, puzzle, slot);
+            }
+            pages.push(page);
+        }
+        if self.do_include_solutions {
+            for (index, chunk) in puzzles.chunks(SOLUTIONS_PER_PAGE).enumerate() {
+                let slots = self.slots(SOLUTIONS_PER_PAGE);
+                let mut page = String::new();
+                writeln!(page, "10 F (Solutions) {} {} R", width - MARGIN, header).unwrap();
+                for (offset, (puzzle, slot)) in chunk.iter().zip(slots).enumerate() {
+                    let number = index * SOLUTIONS_PER_PAGE + offset + 1;
+                    draw_solution(&mut page, number, puzzle, slot);
+                }
+                pages.push(page);
+            }
+        }
+
+        let mut document = String::new();
+        document.push_str("%!PS-Adobe-3.0\n");
+        if let Some(title) = &self.title {
+            writeln!(document, "%%Title: {}", title).unwrap();
+        }
+        document.push_str("%%Creator: nonogram-booklet\n");
+        writeln!(document, "%%Pages: {}", pages.len()).unwrap();
+        writeln!(document, "%%BoundingBox: 0 0 {} {}", width, height).unwrap();
+        document.push_str("%%EndComments\n%%BeginProlog\n");
+        document.push_str(PROLOG);
+        document.push_str("%%EndProlog\n%%BeginSetup\n");
+        writeln!(
+            document,
+            "<< /PageSize [{} {}] >> setpagedevice",
+            width, height
+        )
+        .unwrap();
+        document.push_str("%%EndSetup\n");
+        for (index, page) in pages.iter().enumerate() {
+            let number = index + 1;
+            writeln!(document, "%%Page: {} {}", number, number).unwrap();
+            if let Some(title) = &self.title {
+                writeln!(
+                    document,
+                    "10 F ({}) {} {} L",
+                    ps_string(title),
+                    MARGIN,
+                    header
+                )
+                .unwrap();
+            }
+            writeln!(
+                document,
+                "9 F ({}) {} {} C",
+                number,
+                width / 2.0,
+                MARGIN / 2.0
+            )
+            .unwrap();
+            document.push_str(page);
+            document.push_str("showpage\n");
+        }
+        document.push_str("%%Trailer\n%%EOF\n");
+        document
+    }
+
+    /// `count` equal slots filling the page inside its margins, in rows from
+    /// the top. There are as many rows as columns, or one more.
+    fn slots(&self, count: usize) -> Vec<Slot> {
+        let (page_width, page_height) = self.paper.size();
+        let num_rows = (count as f64).sqrt().ceil() as usize;
+        let num_columns = (count + num_rows - 1) / num_rows;
+        let width =
+            (page_width - 2.0 * MARGIN - GUTTER * (num_columns - 1) as f64) / num_columns as f64;
+        let height =
+            (page_height - 2.0 * MARGIN - GUTTER * (num_rows - 1) as f64) / num_rows as f64;
+        (0..count)
+            .map(|index| {
+                let (row, column) = (index / num_columns, index % num_columns);
+                Slot {
+                    left: MARGIN + column as f64 * (width + GUTTER),
+                    top: page_height - MARGIN - row as f64 * (height + GUTTER),
+                    width,
+                    height,
+                }
+            })
+            .collect()
+    }
+}
+
+/// The puzzle's name, then its grid with the clues above and to the left,
+/// centered in `slot`.
+fn draw_puzzle(page: &mut String, number: usize, puzzle: &Puzzle, slot: Slot) {
+    let file = &puzzle.file;
+    let palette = palette(file);
+    let label_height = 18.0;
+    writeln!(
+        page,
+        "11 F ({}. {}) {:.2} {:.2} L",
+        number,
+        ps_string(&puzzle.name),
+        slot.left,
+        slot.top - 11.0
+    )
+    .unwrap();
+
+    let (width, height) = (file.width as usize, file.height as usize);
+    let num_row_clues = max_num_hints(&file.rows);
+    let num_column_clues = max_num_hints(&file.columns);
+    let units_wide = (num_row_clues + width) as f64;
+    let units_high = (num_column_clues + height) as f64;
+    let cell = (slot.width / units_wide)
+        .min((slot.height - label_height) / units_high)
+        .min(MAX_CELL);
+
+    let grid_left =
+        slot.left + (slot.width - cell * units_wide) / 2.0 + num_row_clues as f64 * cell;
+    let grid_top = slot.top - label_height - num_column_clues as f64 * cell;
+    let font_size = cell * 0.55;
+    writeln!(page, "{:.2} F", font_size).unwrap();
+    let baseline = |center: f64| center - font_size * 0.35;
+
+    for (row, hints) in file.rows.iter().enumerate() {
+        let y = baseline(grid_top - (row as f64 + 0.5) * cell);
+        for (index, text, color) in clues(hints, &palette) {
+            let x = grid_left - (hints.len().max(1) - index) as f64 * cell + cell / 2.0;
+            draw_clue(page, &text, color, x, y);
+        }
+    }
+    for (column, hints) in file.columns.iter().enumerate() {
+        let x = grid_left + (column as f64 + 0.5) * cell;
+        for (index, text, color) in clues(hints, &palette) {
+            let center = grid_top + (hints.len().max(1) - index) as f64 * cell - cell / 2.0;
+            draw_clue(page, &text, color, x, baseline(center));
+        }
+    }
+    draw_grid_lines(page, grid_left, grid_top, width, height, cell);
+}
+
+/// The solution's grid without clues, or a note if there is none.
+fn draw_solution(page: &mut String, number: usize, puzzle: &Puzzle, slot: Slot) {
+    let file = &puzzle.file;
+    let label_height = 14.0;
+    writeln!(
+        page,
+        "9 F ({}. {}) {:.2} {:.2} L",
+        number,
+        ps_string(&puzzle.name),
+        slot.left,
+        slot.top - 9.0
+    )
+    .unwrap();
+    let solution = match &puzzle.solution {
+        Some(solution) => solution,
+        None => {
+            let y = slot.top - label_height - 9.0;
+            writeln!(
+                page,
+                "9 F ({}) {:.2} {:.2} L",
+                ps_string("No solution found"),
+                slot.left,
+                y
+            )
+            .unwrap();
+            return;
+        }
+    };
+    let (width, height) = (file.width as usize, file.height as usize);
+    let cell = (slot.width / width as f64)
+        .min((slot.height - label_height) / height as f64)
+        .min(MAX_SOLUTION_CELL);
+    let grid_left = slot.left + (slot.width - cell * width as f64) / 2.0;
+    let grid_top = slot.top - label_height;
+
+    let palette = palette(file);
+    for row in 0..height {
+        for column in 0..width {
+            let color = match solution.get_tile(row, column) {
+                Some(Tile::FilledWithColor(code)) => palette.get(&code).copied(),
+                Some(Tile::Filled) => None,
+                Some(Tile::NotFilled) | None => continue,
+            };
+            let x = grid_left + column as f64 * cell;
+            let y = grid_top - (row + 1) as f64 * cell;
+            let rectangle = format!("{:.2} {:.2} {:.2} {:.2} rectfill", x, y, cell, cell);
+            draw_in_color(page, &rectangle, color);
+        }
+    }
+    draw_grid_lines(page, grid_left, grid_top, width, height, cell);
+}
+
+/// Thin lines between cells, and thick ones at the edges and every
+/// [GUIDE_EVERY] cells.
+fn draw_grid_lines(page: &mut String, left: f64, top: f64, width: usize, height: usize, cell: f64) {
+    let right = left + width as f64 * cell;
+    let bottom = top - height as f64 * cell;
+    let line_width = |index: usize, last: usize| {
+        if index == 0 || index == last || index % GUIDE_EVERY == 0 {
+            1.2
+        } else {
+            0.4
+        }
+    };
+    for row in 0..=height {
+        let y = top - row as f64 * cell;
+        writeln!(
+            page,
+            "{} setlinewidth {:.2} {:.2} {:.2} {:.2} S",
+            line_width(row, height),
+            left,
+            y,
+            right,
+            y
+        )
+        .unwrap();
+    }
+    for column in 0..=width {
+        let x = left + column as f64 * cell;
+        writeln!(
+            page,
+            "{} setlinewidth {:.2} {:.2} {:.2} {:.2} S",
+            line_width(column, width),
+            x,
+            top,
+            x,
+            bottom
+        )
+        .unwrap();
+    }
+}
+
+fn draw_clue(page: &mut String, text: &str, color: Option<Color>, x: f64, y: f64) {
+    draw_in_color(page, &format!("({}) {:.2} {:.2} C", text, x, y), color);
+}
+
+/// Run `command` in `color`, or in black if it has none. Everything else is
+/// drawn in black.
+fn draw_in_color(page: &mut String, command: &str, color: Option<Color>) {
+    match color {
+        Some(Color { red, green, blue }) => {
+            let channel = |value: u8| f64::from(value) / 255.0;
+            writeln!(
+                page,
+                "{:.3} {:.3} {:.3} setrgbcolor {} 0 setgray",
+                channel(red),
+                channel(green),
+                channel(blue),
+                command
+            )
+            .unwrap();
+        }
+        None => writeln!(page, "{}", command).unwrap(),
+    }
+}
+
+/// The index, text and color of each clue of a line, or a single 0 if it
+/// has none.
+fn clues(hints: &[Hint], palette: &HashMap<char, Color>) -> Vec<(usize, String, Option<Color>)> {
+    if hints.is_empty() {
+        return vec![(0, "0".to_string(), None)];
+    }
+    hints
+        .iter()
+        .enumerate()
+        .map(|(index, hint)| {
+            let color = hint
+                .color
+                .and_then(|ColorCode(code)| palette.get(&code).copied());
+            (index, hint.length.to_string(), color)
+        })
+        .collect()
+}
+
+fn max_num_hints(lines: &[Vec<Hint>]) -> usize {
+    lines
+        .iter()
+        .map(|hints| hints.len().max(1))
+        .max()
+        .unwrap_or(1)
+}
+
+fn palette(file: &NonogramFile) -> HashMap<char, Color> {
+    file.colors
+        .iter()
+        .flatten()
+        .map(|definition| {
+            let ColorCode(code) = definition.code;
+            (code, definition.color)
+        })
+        .collect()
+}
+
+/// `s` as the inside of a PostScript string. The standard fonts only have
+/// ASCII, so anything else becomes a question mark.
+fn ps_string(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            ' '..='~' => escaped.push(c),
+            _ => escaped.push('?'),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nonogram_parser::parse_non;
+
+    fn puzzles(count: usize) -> Vec<Puzzle> {
+        let file = parse_non("width 2\nheight 1\nrows\n1\ncolumns\n1\n0\n").unwrap();
+        (0..count)
+            .map(|index| Puzzle {
+                name: format!("puzzle {}", index),
+                file: file.clone(),
+                solution: None,
+            })
+            .collect()
+    }
+
+    fn num_pages(document: &str) -> usize {
+        document
+            .lines()
+            .find_map(|line| line.strip_prefix("%%Pages: "))
+            .unwrap()
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn slots_fill_the_page_inside_the_margins() {
+        for &paper in &[Paper::A4, Paper::Letter] {
+            let (page_width, page_height) = paper.size();
+            let booklet = Booklet::new(paper);
+            for count in 1..=SOLUTIONS_PER_PAGE {
+                let slots = booklet.slots(count);
+                assert_eq!(slots.len(), count);
+                for slot in slots {
+                    assert!(slot.left >= MARGIN, "{:?}", slot);
+                    assert!(slot.left + slot.width <= page_width - MARGIN + 1e-9);
+                    assert!(slot.top <= page_height - MARGIN);
+                    assert!(slot.top - slot.height >= MARGIN - 1e-9);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn ps_string_escapes_and_replaces_non_ascii() {
+        assert_eq!(ps_string("a (b) \\c"), "a \\(b\\) \\\\c");
+        assert_eq!(ps_string("caf\u{e9} \u{1f600}"), "caf? ?");
+    }
+
+    #[test]
+    fn empty_line_has_a_lone_zero_clue() {
+        let clues = clues(&[], &HashMap::new());
+        assert_eq!(clues, vec![(0, "0".to_string(), None)]);
+    }
+
+    #[test]
+    fn pages_are_counted_with_and_without_solutions() {
+        let booklet = Booklet::new(Paper::A4).with_per_page(4);
+        let puzzles = puzzles(13);
+        assert_eq!(num_pages(&booklet.write(&puzzles)), 4 + 2);
+        let booklet = booklet.with_solutions(false);
+        let document = booklet.write(&puzzles);
+        assert_eq!(num_pages(&document), 4);
+        assert_eq!(document.matches("%%Page: ").count(), 4);
+    }
+}
@@ -0,0 +1,132 @@
+mod booklet;
+
+use crate::booklet::{Booklet, Paper, Puzzle};
+use nonogram_parser::Format;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+const USAGE: &str = "\
+Usage:
+    nonogram-booklet [options] DIRECTORY [OUTPUT]
+
+Lays out every puzzle file (.non, .json or .xml) in DIRECTORY, in order of
+file name, as a printable PostScript booklet with the solutions at the end.
+The booklet is written to OUTPUT, or printed if there is no OUTPUT.
+
+Options:
+    --per-page N    How many puzzles to put on each page (default 1)
+    --paper PAPER   The paper size: a4 or letter (default a4)
+    --title TITLE   A title to print at the top of every page
+    --no-solutions  Leave out the solutions
+    -h, --help      Print this message";
+
+pub fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut booklet = Booklet::new(Paper::A4);
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            "--no-solutions" => booklet = booklet.with_solutions(false),
+            "--per-page" | "--paper" | "--title" => {
+                let value = match args.next() {
+                    Some(value) => value.as_str(),
+                    None => usage_error(&format!("{} needs a value", arg)),
+                };
+                booklet = match (arg.as_str(), value) {
+                    ("--per-page", value) => match value.parse() {
+                        Ok(per_page) if per_page > 0 => booklet.with_per_page(per_page),
+                        _ => usage_error(&format!("invalid number of puzzles \"{}\"", value)),
+                    },
+                    ("--paper", "a4") => booklet.with_paper(Paper::A4),
+                    ("--paper", "letter") => booklet.with_paper(Paper::Letter),
+                    ("--paper", value) => usage_error(&format!(
+                        "unknown paper size \"{}\" (expected a4 or letter)",
+                        value
+                    )),
+                    (_, value) => booklet.with_title(Some(value.to_string())),
+                };
+            }
+            _ if arg.starts_with("--") => usage_error(&format!("unknown option \"{}\"", arg)),
+            _ => paths.push(arg.as_str()),
+        }
+    }
+    let (directory, output) = match paths.as_slice() {
+        [directory] => (Path::new(directory), None),
+        [directory, output] => (Path::new(directory), Some(Path::new(output))),
+        _ => usage_error("needs a directory and at most one output"),
+    };
+
+    let puzzles = match load_puzzles(directory) {
+        Ok(puzzles) => puzzles,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            process::exit(1);
+        }
+    };
+    if puzzles.is_empty() {
+        eprintln!("error: {}: no puzzle files", directory.display());
+        process::exit(1);
+    }
+    let document = booklet.write(&puzzles);
+    match output {
+        Some(output) => {
+            if let Err(e) = fs::write(output, document) {
+                eprintln!("error: {}: {}", output.display(), e);
+                process::exit(1);
+            }
+        }
+        None => print!("{}", document),
+    }
+}
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}\n\n{}", message, USAGE);
+    process::exit(2);
+}
+
+/// Every puzzle file in `directory`, in order of file name, and its
+/// solution. Files that fail to load are skipped with a warning.
+fn load_puzzles(directory: &Path) -> Result<Vec<Puzzle>, String> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(directory)
+        .map_err(|e| format!("{}: {}", directory.display(), e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| Format::from_path(path).is_some())
+        .collect();
+    paths.sort();
+
+    let mut puzzles = Vec::with_capacity(paths.len());
+    for path in &paths {
+        let file = match nonogram_parser::load(path) {
+            Ok(file) => file,
+            Err(e) => {
+                eprintln!("warning: {}: skipped: {}", path.display(), e);
+                continue;
+            }
+        };
+        let solution = file.solve(1).pop();
+        if solution.is_none() {
+            eprintln!("warning: {}: no solution was found", path.display());
+        }
+        let name = match &file.title {
+            Some(title) => title.clone(),
+            None => path
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned(),
+        };
+        puzzles.push(Puzzle {
+            name,
+            file,
+            solution,
+        });
+    }
+    Ok(puzzles)
+}
@@ -0,0 +1,122 @@
+//! Reading puzzle files in any supported format
+//!
+//! The format of a file is chosen by its extension:
+//!
+//! - `.non`: the [`.non` format](crate::parser)
+//! - `.json`: a serialized [NonogramFile]
+//! - `.xml`: the [webpbn XML format](crate::webpbn)
+
+use crate::parser::{self, ParseError, Warning};
+use crate::webpbn;
+use nonogram::nonogram_file::{self, NonogramFile};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Format {
+    Non,
+    Json,
+    Xml,
+}
+
+impl Format {
+    pub const ALL: [Format; 3] = [Format::Non, Format::Json, Format::Xml];
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Non => "non",
+            Format::Json => "json",
+            Format::Xml => "xml",
+        }
+    }
+
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        Format::ALL
+            .iter()
+            .cloned()
+            .find(|format| format.extension().eq_ignore_ascii_case(extension))
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(Format::from_extension)
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.extension())
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(PathBuf, io::Error),
+    UnknownFormat(PathBuf),
+    Parse(ParseError),
+    Json(serde_json::Error),
+    /// A JSON file whose hints or goal don't match its dimensions.
+    Invalid(nonogram_file::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            Error::UnknownFormat(path) => {
+                write!(f, "{}: unknown puzzle file format", path.display())
+            }
+            Error::Parse(e) => write!(f, "{}", e),
+            Error::Json(e) => write!(f, "{}", e),
+            Error::Invalid(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub fn parse(input: &str, format: Format) -> Result<NonogramFile, Error> {
+    parse_with_warnings(input, format).map(|(file, _)| file)
+}
+
+pub fn parse_with_warnings(
+    input: &str,
+    format: Format,
+) -> Result<(NonogramFile, Vec<Warning>), Error> {
+    match format {
+        Format::Non => parser::parse_non_with_warnings(input).map_err(Error::Parse),
+        Format::Json => {
+            let file: NonogramFile = serde_json::from_str(input).map_err(Error::Json)?;
+            file.validate().map_err(Error::Invalid)?;
+            Ok((file, Vec::new()))
+        }
+        Format::Xml => webpbn::parse_webpbn(input)
+            .map(|file| (file, Vec::new()))
+            .map_err(Error::Parse),
+    }
+}
+
+/// Read the puzzle file at `path`, choosing its format from its extension.
+pub fn load(path: &Path) -> Result<NonogramFile, Error> {
+    load_with_warnings(path).map(|(file, _)| file)
+}
+
+pub fn load_with_warnings(path: &Path) -> Result<(NonogramFile, Vec<Warning>), Error> {
+    let format = Format::from_path(path).ok_or_else(|| Error::UnknownFormat(path.to_owned()))?;
+    let input = fs::read_to_string(path).map_err(|e| Error::Io(path.to_owned(), e))?;
+    parse_with_warnings(&input, format)
+}
+
+/// Write `file` in `format`.
+pub fn to_string(file: &NonogramFile, format: Format) -> String {
+    match format {
+        Format::Non => parser::to_non_string(file),
+        Format::Json => {
+            serde_json::to_string_pretty(file).expect("a NonogramFile always serializes")
+        }
+        Format::Xml => webpbn::to_webpbn_string(file),
+    }
+}
@@ -1,5 +1,341 @@
-use nonogram_parser;
+use nonogram::html::HtmlRenderer;
+use nonogram::nonogram_file::{Cell, Hint, NonogramFile};
+use nonogram::solver;
+use nonogram::{Line, Nonogram};
+use nonogram_parser::format::{self, load_with_warnings, Format};
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process;
+
+const USAGE: &str = "\
+Usage:
+    nonogram-parser validate [--strict] FILE...
+    nonogram-parser convert [--to FORMAT] INPUT [OUTPUT]
+    nonogram-parser info FILE...
+    nonogram-parser html [--solution] INPUT [OUTPUT]
+
+Commands:
+    validate  Parse each file and report errors and warnings
+    convert   Convert INPUT to the format of OUTPUT's extension, or print it
+              in FORMAT if there is no OUTPUT
+    info      Print each file's metadata, dimensions and colors
+    html      Write INPUT as a standalone HTML page to OUTPUT, or print it
+
+Options:
+    --strict     Treat warnings as errors
+    --to FORMAT  The format to convert to: non, json or xml
+    --solution   Add a page with the solution: the goal, or the solver's
+                 solution if there is no goal
+    -h, --help   Print this message
+
+The format of each file is chosen by its extension: .non, .json or .xml.
+Exits with a non-zero status if any file has errors.";
 
 fn main() {
-    nonogram_parser::main();
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (command, rest) = match args.split_first() {
+        Some((command, rest)) => (command.as_str(), rest),
+        None => ("--help", &args[..]),
+    };
+    let result = match command {
+        "-h" | "--help" => {
+            println!("{}", USAGE);
+            Ok(true)
+        }
+        "validate" => validate(rest),
+        "convert" => convert(rest),
+        "info" => info(rest),
+        "html" => html(rest),
+        _ => Err(format!("unknown command \"{}\"", command)),
+    };
+    match result {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            process::exit(2);
+        }
+    }
+}
+
+/// Each flag or option found, with the option's value.
+type Found<'a> = Vec<(&'a str, Option<&'a str>)>;
+
+/// Split `args` into flags and their values, and the remaining paths.
+fn split_options<'a>(
+    args: &'a [String],
+    flags: &[&str],
+    options: &[&str],
+) -> Result<(Found<'a>, Vec<&'a str>), String> {
+    let mut found = Vec::new();
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if flags.contains(&arg.as_str()) {
+            found.push((arg.as_str(), None));
+        } else if options.contains(&arg.as_str()) {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("{} needs a value", arg))?;
+            found.push((arg.as_str(), Some(value.as_str())));
+        } else if arg.starts_with("--") {
+            return Err(format!("unknown option \"{}\"", arg));
+        } else {
+            paths.push(arg.as_str());
+        }
+    }
+    Ok((found, paths))
+}
+
+fn validate(args: &[String]) -> Result<bool, String> {
+    let (flags, paths) = split_options(args, &["--strict"], &[])?;
+    let strict = !flags.is_empty();
+    if paths.is_empty() {
+        return Err("validate needs at least one file".to_string());
+    }
+    let mut num_invalid = 0;
+    for path in &paths {
+        let (errors, warnings) = match load_with_warnings(Path::new(path)) {
+            Ok((file, warnings)) => {
+                let mut warnings: Vec<String> = warnings.iter().map(|w| w.to_string()).collect();
+                if file.goal.is_none() {
+                    warnings.push("no goal is given".to_string());
+                }
+                (check(&file), warnings)
+            }
+            Err(e) => (vec![e.to_string()], Vec::new()),
+        };
+        for error in &errors {
+            println!("{}: error: {}", path, error);
+        }
+        for warning in &warnings {
+            println!("{}: warning: {}", path, warning);
+        }
+        if !errors.is_empty() || (strict && !warnings.is_empty()) {
+            num_invalid += 1;
+        } else if warnings.is_empty() {
+            println!("{}: ok", path);
+        }
+    }
+    if paths.len() > 1 {
+        println!(
+            "{} of {} files are valid",
+            paths.len() - num_invalid,
+            paths.len()
+        );
+    }
+    Ok(num_invalid == 0)
+}
+
+/// Problems that make a parsed file unplayable.
+fn check(file: &NonogramFile) -> Vec<String> {
+    if let Err(e) = file.validate() {
+        return vec![e.to_string()];
+    }
+    let mut errors = Vec::new();
+    let non = file.to_nonogram();
+    let row_clues = (0..non.num_rows())
+        .map(|row| non.colored_clues(Line::Row(row)))
+        .collect();
+    let column_clues = (0..non.num_cols())
+        .map(|column| non.colored_clues(Line::Column(column)))
+        .collect();
+    if let Err(e) = Nonogram::try_colored(row_clues, column_clues) {
+        errors.push(e.to_string());
+    }
+
+    let defined: Option<Vec<char>> = file
+        .colors
+        .as_ref()
+        .map(|colors| colors.iter().map(|color| color.code.0).collect());
+    let hints = file.rows.iter().chain(file.columns.iter()).flatten();
+    for hint in hints {
+        if let (Some(defined), Some(code)) = (&defined, &hint.color) {
+            if !defined.contains(&code.0) {
+                errors.push(format!("color \"{}\" is used but not defined", code.0));
+                break;
+            }
+        }
+    }
+
+    if let Some(goal) = &file.goal {
+        let width = file.width as usize;
+        let goal_rows: Vec<Vec<&Cell>> = (0..file.height as usize)
+            .map(|row| goal[row * width..(row + 1) * width].iter().collect())
+            .collect();
+        for (index, hints) in file.rows.iter().enumerate() {
+            if goal_hints(&goal_rows[index]) != *hints {
+                errors.push(format!(
+                    "the goal does not match the hints of row {}",
+                    index
+                ));
+            }
+        }
+        for (index, hints) in file.columns.iter().enumerate() {
+            let column: Vec<&Cell> = goal_rows.iter().map(|row| row[index]).collect();
+            if goal_hints(&column) != *hints {
+                errors.push(format!(
+                    "the goal does not match the hints of column {}",
+                    index
+                ));
+            }
+        }
+    }
+    errors
+}
+
+/// The hints for a line of the goal, where each run of cells of the same color
+/// is one hint.
+fn goal_hints(cells: &[&Cell]) -> Vec<Hint> {
+    let mut hints: Vec<Hint> = Vec::new();
+    let mut previous = &Cell::Empty;
+    for &cell in cells {
+        if let Cell::Filled(color) = cell {
+            match hints.last_mut() {
+                Some(hint) if cell == previous => hint.length += 1,
+                _ => hints.push(Hint {
+                    length: 1,
+                    color: *color,
+                }),
+            }
+        }
+        previous = cell;
+    }
+    hints
+}
+
+fn convert(args: &[String]) -> Result<bool, String> {
+    let (options, paths) = split_options(args, &[], &["--to"])?;
+    let (input, output) = match paths.as_slice() {
+        [input] => (input, None),
+        [input, output] => (input, Some(Path::new(output))),
+        _ => return Err("convert needs an input file and at most one output".to_string()),
+    };
+    let format = match (options.first(), output) {
+        (Some((_, Some(name))), _) => {
+            Format::from_extension(name).ok_or_else(|| format!("unknown format \"{}\"", name))?
+        }
+        (_, Some(output)) => Format::from_path(output)
+            .ok_or_else(|| format!("{}: unknown puzzle file format", output.display()))?,
+        (_, None) => return Err("convert needs --to when there is no output file".to_string()),
+    };
+
+    let (file, warnings) = match load_with_warnings(Path::new(input)) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("{}: error: {}", input, e);
+            return Ok(false);
+        }
+    };
+    for warning in &warnings {
+        eprintln!("{}: warning: {}", input, warning);
+    }
+    let converted = format::to_string(&file, format);
+    match output {
+        Some(output) => {
+            if let Err(e) = fs::write(output, converted) {
+                eprintln!("{}: error: {}", output.display(), e);
+                return Ok(false);
+            }
+        }
+        None => print!("{}", converted),
+    }
+    Ok(true)
+}
+
+fn info(args: &[String]) -> Result<bool, String> {
+    let (_, paths) = split_options(args, &[], &[])?;
+    if paths.is_empty() {
+        return Err("info needs at least one file".to_string());
+    }
+    let mut succeeded = true;
+    for (index, path) in paths.iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+        let file = match format::load(Path::new(path)) {
+            Ok(file) => file,
+            Err(e) => {
+                eprintln!("{}: error: {}", path, e);
+                succeeded = false;
+                continue;
+            }
+        };
+        println!("{}", path);
+        let metadata = [
+            ("title", &file.title),
+            ("by", &file.by),
+            ("catalogue", &file.catalogue),
+            ("copyright", &file.copyright),
+            ("license", &file.license),
+        ];
+        for (key, value) in metadata.iter() {
+            if let Some(value) = value {
+                println!("  {}: {}", key, value);
+            }
+        }
+        println!("  size: {}x{}", file.width, file.height);
+        let num_hints: usize = file
+            .rows
+            .iter()
+            .chain(file.columns.iter())
+            .map(Vec::len)
+            .sum();
+        println!("  hints: {}", num_hints);
+        match &file.colors {
+            Some(colors) => {
+                println!("  colors: {}", colors.len());
+                for definition in colors {
+                    let color = definition.color;
+                    println!(
+                        "    {} #{:02x}{:02x}{:02x}",
+                        definition.code.0, color.red, color.green, color.blue
+                    );
+                }
+            }
+            None => println!("  colors: none (black and white)"),
+        }
+        println!("  goal: {}", if file.goal.is_some() { "yes" } else { "no" });
+    }
+    Ok(succeeded)
+}
+
+fn html(args: &[String]) -> Result<bool, String> {
+    let (flags, paths) = split_options(args, &["--solution"], &[])?;
+    let (input, output) = match paths.as_slice() {
+        [input] => (input, None),
+        [input, output] => (input, Some(Path::new(output))),
+        _ => return Err("html needs an input file and at most one output".to_string()),
+    };
+    let file = match format::load(Path::new(input)) {
+        Ok(file) => file,
+        Err(e) => {
+            eprintln!("{}: error: {}", input, e);
+            return Ok(false);
+        }
+    };
+    let with_solution = !flags.is_empty();
+    let solution = if with_solution && file.goal.is_none() {
+        let mut search = solver::search(&file.to_nonogram(), 1);
+        if search.solutions.is_empty() {
+            eprintln!("{}: warning: no solution was found", input);
+        }
+        search.solutions.pop()
+    } else {
+        None
+    };
+    let page = HtmlRenderer::default()
+        .with_solution(with_solution)
+        .render(&file, solution.as_ref());
+    match output {
+        Some(output) => {
+            if let Err(e) = fs::write(output, page) {
+                eprintln!("{}: error: {}", output.display(), e);
+                return Ok(false);
+            }
+        }
+        None => print!("{}", page),
+    }
+    Ok(true)
 }
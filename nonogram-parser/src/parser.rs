@@ -1,161 +1,316 @@
-// use nonogram;
-
-// Structs
-pub struct NonogramFile {
-    pub width: u64,
-    pub height: u64,
-    pub rows: Vec<Hint>,
-    pub columns: Vec<Hint>,
-
-    pub goal: Option<Cell>,
-
-    pub catalogue: Option<String>,
-    pub title: Option<String>,
-    pub by: Option<String>,
-    pub copyright: Option<String>,
-    pub license: Option<String>,
-    pub colors: Option<Vec<ColorDefinition>>,
-}
+//! Parsing the `.non` format
+//!
+//! A `.non` file is a list of `key value` lines, as described in the [format
+//! definition](https://github.com/mikix/nonogram-db/blob/master/FORMAT.md):
+//!
+//! ```text
+//! title "Tiny"
+//! width 3
+//! height 2
+//!
+//! rows
+//! 3
+//! 1,1
+//!
+//! columns
+//! 2
+//! 1
+//! 2
+//!
+//! goal "111101"
+//! ```
+//!
+//! `rows` and `columns` are followed by one line of comma separated hints per
+//! line of the puzzle, with `0` for a line with no hints. In the goal, `0` is
+//! an empty tile and `1` a filled one. Lines starting with `#` are comments,
+//! and unknown keys are skipped with a [Warning].
+//!
+//! Colored puzzles are written with `color <code> <rrggbb>` lines, a color
+//! code after a hint's length (like `2a`), and the color code in place of `1`
+//! in the goal.
+
+use nonogram::nonogram_file::{
+    self, Cell, Color, ColorCode, ColorDefinition, Hint, NonogramFile, NonogramFileBuilder,
+};
+use std::fmt;
 
-pub enum Cell {
-    Empty,
-    Filled(Option<ColorCode>),
+/// Something wrong with a file that keeps it from being parsed.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ParseError {
+    /// The 1-based line the error was found on, or [None] if it is about the
+    /// file as a whole.
+    pub line: Option<usize>,
+    pub message: String,
 }
 
-pub struct Hint {
-    pub length: u64,
-    pub color: Option<ColorCode>,
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
 }
 
-pub struct ColorCode(pub char);
+impl std::error::Error for ParseError {}
 
-pub struct ColorDefinition {
-    pub code: ColorCode,
-    pub color: Color,
+/// Something unusual about a file that was still parsed.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Warning {
+    pub line: usize,
+    pub message: String,
 }
 
-pub struct Color {
-    pub red: u8,
-    pub green: u8,
-    pub blue: u8,
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
 }
 
-// Builder
-pub struct NonogramFileBuilder {
-    pub width: Option<u64>,
-    pub height: Option<u64>,
-    pub rows: Option<Vec<Hint>>,
-    pub columns: Option<Vec<Hint>>,
-
-    pub goal: Option<Vec<Cell>>,
-
-    pub catalogue: Option<String>,
-    pub title: Option<String>,
-    pub by: Option<String>,
-    pub copyright: Option<String>,
-    pub license: Option<String>,
-    pub colors: Option<Vec<ColorDefinition>>,
+pub fn parse_non(input: &str) -> Result<NonogramFile, ParseError> {
+    parse_non_with_warnings(input).map(|(file, _)| file)
 }
 
-macro_rules! add_value_fn {
-    ( $key:ident<&str> ) => {
-        pub fn $key(&mut self, $key: &str) -> BuildResult {
-            match self.$key {
-                None => {
-                    self.$key = Some($key.to_string());
-                    Ok(self)
-                }
-                Some(_) => Err(Error::DuplicateValue(
-                    stringify!($key).to_string())),
+/// Parse a `.non` file, also returning anything unusual that was skipped.
+pub fn parse_non_with_warnings(input: &str) -> Result<(NonogramFile, Vec<Warning>), ParseError> {
+    let mut builder = NonogramFileBuilder::new();
+    let mut warnings = Vec::new();
+    let mut colors = Vec::new();
+    let mut lines = input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+    while let Some((line_number, line)) = lines.next() {
+        let error = |message: String| ParseError {
+            line: Some(line_number),
+            message,
+        };
+        let build_error = |e: nonogram_file::Error| error(e.to_string());
+        let (key, value) = first_word_and_rest(line);
+        match key {
+            "catalogue" => {
+                builder.catalogue(&unquote(value)).map_err(build_error)?;
             }
-        }
-    };
-    ( $key:ident<$type:ty> ) => {
-        pub fn $key(&mut self, $key: $type) -> BuildResult {
-            match self.$key {
-                None => {
-                    self.$key = Some($key);
-                    Ok(self)
-                }
-                Some(_) => Err(Error::DuplicateValue(
-                    stringify!($key).to_string())),
+            "title" => {
+                builder.title(&unquote(value)).map_err(build_error)?;
             }
-        }
-    };
-}
-
-macro_rules! add_value_fn_with_required {
-    ( $key:ident<$type:ty> $(, $required:ident )* ) => {
-        pub fn $key(&mut self, $key: $type) -> BuildResult {
-            $(
-                if self.$required.is_none() {
-                    return Err(Error::ThisNeededRequiredValue(
-                        stringify!($key).to_string(),
-                        stringify!($required).to_string(),
-                    ));
+            "by" => {
+                builder.by(&unquote(value)).map_err(build_error)?;
+            }
+            "copyright" => {
+                builder.copyright(&unquote(value)).map_err(build_error)?;
+            }
+            "license" => {
+                builder.license(&unquote(value)).map_err(build_error)?;
+            }
+            "width" => {
+                builder
+                    .width(parse_number(value).map_err(error)?)
+                    .map_err(build_error)?;
+            }
+            "height" => {
+                builder
+                    .height(parse_number(value).map_err(error)?)
+                    .map_err(build_error)?;
+            }
+            "rows" | "columns" => {
+                let count = if key == "rows" {
+                    builder.height
+                } else {
+                    builder.width
+                };
+                let count = count.ok_or_else(|| {
+                    let required = if key == "rows" { "height" } else { "width" };
+                    build_error(nonogram_file::Error::ThisNeededRequiredValue(
+                        key.to_string(),
+                        required.to_string(),
+                    ))
+                })?;
+                let mut hints = Vec::with_capacity(count as usize);
+                for _ in 0..count {
+                    let (hint_line_number, hint_line) = lines.next().ok_or_else(|| {
+                        error(format!(
+                            "expected {} lines of hints after \"{}\"",
+                            count, key
+                        ))
+                    })?;
+                    let line_hints = parse_hints(hint_line).map_err(|message| ParseError {
+                        line: Some(hint_line_number),
+                        message,
+                    })?;
+                    hints.push(line_hints);
                 }
-            )*
-            match self.$key {
-                None => {
-                    self.$key = Some($key);
-                    Ok(self)
+                if key == "rows" {
+                    builder.rows(hints).map_err(build_error)?;
+                } else {
+                    builder.columns(hints).map_err(build_error)?;
                 }
-                Some(_) => Err(Error::DuplicateValue(
-                    stringify!($key).to_string())),
             }
+            "goal" => {
+                builder
+                    .goal(parse_goal(&unquote(value)))
+                    .map_err(build_error)?;
+            }
+            "color" => colors.push(parse_color(value).map_err(error)?),
+            _ => warnings.push(Warning {
+                line: line_number,
+                message: format!("unknown key \"{}\" was skipped", key),
+            }),
         }
-    };
+    }
+
+    if !colors.is_empty() {
+        builder.colors(colors).map_err(|e| ParseError {
+            line: None,
+            message: e.to_string(),
+        })?;
+    }
+    let file = builder.build().map_err(|e| ParseError {
+        line: None,
+        message: e.to_string(),
+    })?;
+    Ok((file, warnings))
 }
 
-impl NonogramFileBuilder {
-    pub fn new() -> Self {
-        NonogramFileBuilder {
-            width: None,
-            height: None,
-            rows: None,
-            columns: None,
-            goal: None,
-            catalogue: None,
-            title: None,
-            by: None,
-            copyright: None,
-            license: None,
-            colors: None,
+/// Write `file` in the `.non` format.
+pub fn to_non_string(file: &NonogramFile) -> String {
+    let mut lines = Vec::new();
+    let strings = [
+        ("catalogue", &file.catalogue),
+        ("title", &file.title),
+        ("by", &file.by),
+        ("copyright", &file.copyright),
+        ("license", &file.license),
+    ];
+    for (key, value) in strings.iter() {
+        if let Some(value) = value {
+            lines.push(format!("{} \"{}\"", key, value));
         }
     }
+    lines.push(format!("width {}", file.width));
+    lines.push(format!("height {}", file.height));
+    for definition in file.colors.iter().flatten() {
+        let Color { red, green, blue } = definition.color;
+        lines.push(format!(
+            "color {} {:02x}{:02x}{:02x}",
+            definition.code.0, red, green, blue
+        ));
+    }
+    for (key, hints) in &[("rows", &file.rows), ("columns", &file.columns)] {
+        lines.push(String::new());
+        lines.push(key.to_string());
+        lines.extend(hints.iter().map(|line| format_hints(line)));
+    }
+    if let Some(goal) = &file.goal {
+        let cells: String = goal
+            .iter()
+            .map(|cell| match cell {
+                Cell::Empty => '0',
+                Cell::Filled(None) => '1',
+                Cell::Filled(Some(ColorCode(code))) => *code,
+            })
+            .collect();
+        lines.push(String::new());
+        lines.push(format!("goal \"{}\"", cells));
+    }
+    lines.push(String::new());
+    lines.join("\n")
+}
 
-    add_value_fn!(width<u64>);
-    add_value_fn!(height<u64>);
-
-    add_value_fn_with_required!(rows<Vec<Hint>>, width, height);
-    add_value_fn_with_required!(columns<Vec<Hint>>, width, height);
-    add_value_fn_with_required!(goal<Vec<Cell>>, width, height);
+fn format_hints(hints: &[Hint]) -> String {
+    if hints.is_empty() {
+        return "0".to_string();
+    }
+    let strings: Vec<_> = hints
+        .iter()
+        .map(|hint| match &hint.color {
+            Some(ColorCode(code)) => format!("{}{}", hint.length, code),
+            None => hint.length.to_string(),
+        })
+        .collect();
+    strings.join(",")
+}
 
-    add_value_fn!(catalogue<&str>);
-    add_value_fn!(title<&str>);
-    add_value_fn!(by<&str>);
-    add_value_fn!(copyright<&str>);
-    add_value_fn!(license<&str>);
+fn parse_number(value: &str) -> Result<u64, String> {
+    value
+        .parse()
+        .map_err(|_| format!("expected a number but got \"{}\"", value))
 }
 
-pub enum Error {
-    DuplicateValue(String),
-    ThisNeededRequiredValue(String, String),
+fn parse_hints(line: &str) -> Result<Vec<Hint>, String> {
+    let mut hints = Vec::new();
+    for hint in line.split(',').map(str::trim) {
+        let digits_end = hint
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(hint.len());
+        let (digits, code) = hint.split_at(digits_end);
+        let length = parse_number(digits).map_err(|_| format!("invalid hint \"{}\"", hint))?;
+        let mut code_chars = code.trim().chars();
+        let color = match (code_chars.next(), code_chars.next()) {
+            (None, _) => None,
+            (Some(code), None) => Some(ColorCode(code)),
+            _ => return Err(format!("invalid hint \"{}\"", hint)),
+        };
+        // A lone 0 means the line has no hints.
+        if length > 0 {
+            hints.push(Hint { length, color });
+        }
+    }
+    Ok(hints)
 }
 
-type Result<T> = std::result::Result<T, Error>;
-type BuildResult<'a> = Result<&'a NonogramFileBuilder>;
+fn parse_goal(value: &str) -> Vec<Cell> {
+    value
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| match c {
+            '0' => Cell::Empty,
+            '1' => Cell::Filled(None),
+            code => Cell::Filled(Some(ColorCode(code))),
+        })
+        .collect()
+}
 
-// Main
-pub fn main() {
-    let input = "key this is the value";
-    println!("{:?}", first_word_and_rest(input));
+fn parse_color(value: &str) -> Result<ColorDefinition, String> {
+    let (code, rgb) = first_word_and_rest(value);
+    let mut code_chars = code.chars();
+    let code = match (code_chars.next(), code_chars.next()) {
+        (Some(code), None) => code,
+        _ => return Err(format!("invalid color code \"{}\"", code)),
+    };
+    let rgb = rgb.trim_start_matches('#');
+    let channel = |index: usize| {
+        rgb.get(index..index + 2)
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            .ok_or_else(|| format!("invalid color \"{}\"", rgb))
+    };
+    if rgb.len() != 6 {
+        return Err(format!("invalid color \"{}\"", rgb));
+    }
+    Ok(ColorDefinition {
+        code: ColorCode(code),
+        color: Color {
+            red: channel(0)?,
+            green: channel(2)?,
+            blue: channel(4)?,
+        },
+    })
+}
 
-    println!("finished parser");
+/// Remove the quotes around a value, if it has them.
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+        value[1..value.len() - 1].to_string()
+    } else {
+        value.to_string()
+    }
 }
 
 fn first_word_and_rest(input: &str) -> (&str, &str) {
     let index = input.find(' ').unwrap_or(input.len());
-    let (first, reset) = input.split_at(index);
-    (first, reset.trim_start())
+    let (first, rest) = input.split_at(index);
+    (first, rest.trim_start())
 }
@@ -0,0 +1,311 @@
+//! Reading and writing the [webpbn](https://webpbn.com/pbn_fmt.html) XML
+//! format
+//!
+//! Only the first `<puzzle>` of a `<puzzleset>` is read. Puzzles with a single
+//! color besides the background are read as uncolored. Otherwise each color's
+//! `char` becomes its color code, and hints without a `color` use the
+//! puzzle's default color. White `.` and black `X` are predefined, and a
+//! `<color>` element with the same name or `char` replaces them.
+
+use crate::parser::ParseError;
+use nonogram::nonogram_file::{Cell, Color, ColorCode, ColorDefinition, Hint, NonogramFile};
+use roxmltree::{Document, Node};
+
+const BACKGROUND_CODE: char = '.';
+const FILLED_CODE: char = 'X';
+
+struct XmlColor {
+    name: String,
+    code: char,
+    color: Color,
+    predefined: bool,
+}
+
+fn predefined_colors() -> Vec<XmlColor> {
+    let color = |name: &str, code, value| XmlColor {
+        name: name.to_string(),
+        code,
+        color: Color {
+            red: value,
+            green: value,
+            blue: value,
+        },
+        predefined: true,
+    };
+    vec![
+        color("white", BACKGROUND_CODE, 255),
+        color("black", FILLED_CODE, 0),
+    ]
+}
+
+pub fn parse_webpbn(input: &str) -> Result<NonogramFile, ParseError> {
+    let document = Document::parse(input).map_err(|e| ParseError {
+        line: Some(e.pos().row as usize),
+        message: e.to_string(),
+    })?;
+    let puzzle = document
+        .descendants()
+        .find(|node| node.has_tag_name("puzzle"))
+        .ok_or_else(|| error(None, "no <puzzle> element"))?;
+
+    let default_name = puzzle.attribute("defaultcolor").unwrap_or("black");
+    let background_name = puzzle.attribute("backgroundcolor").unwrap_or("white");
+    let mut colors = predefined_colors();
+    for node in children(puzzle, "color") {
+        let name = node
+            .attribute("name")
+            .ok_or_else(|| error(Some(node), "<color> needs a name"))?;
+        let code = node
+            .attribute("char")
+            .and_then(|code| code.chars().next())
+            .ok_or_else(|| error(Some(node), "<color> needs a char"))?;
+        let color = parse_rgb(node.text().unwrap_or("").trim())
+            .ok_or_else(|| error(Some(node), "invalid color value"))?;
+        colors.retain(|known| !known.predefined || (known.name != name && known.code != code));
+        colors.push(XmlColor {
+            name: name.to_string(),
+            code,
+            color,
+            predefined: false,
+        });
+    }
+    // A predefined color only makes the puzzle colored if it is used as the
+    // default.
+    let filled_colors: Vec<&XmlColor> = colors
+        .iter()
+        .filter(|color| color.name != background_name)
+        .filter(|color| !color.predefined || color.name == default_name)
+        .collect();
+    let is_colored = filled_colors.len() > 1;
+    let code_of = |node: Node, name: &str| -> Result<Option<ColorCode>, ParseError> {
+        if !is_colored {
+            return Ok(None);
+        }
+        filled_colors
+            .iter()
+            .find(|color| color.name == name)
+            .map(|color| Some(ColorCode(color.code)))
+            .ok_or_else(|| error(Some(node), &format!("unknown color \"{}\"", name)))
+    };
+
+    let mut rows = None;
+    let mut columns = None;
+    for clues in children(puzzle, "clues") {
+        let mut lines = Vec::new();
+        for line in children(clues, "line") {
+            let mut hints = Vec::new();
+            for count in children(line, "count") {
+                let length = count
+                    .text()
+                    .unwrap_or("")
+                    .trim()
+                    .parse()
+                    .map_err(|_| error(Some(count), "invalid count"))?;
+                let color = code_of(count, count.attribute("color").unwrap_or(default_name))?;
+                hints.push(Hint { length, color });
+            }
+            lines.push(hints);
+        }
+        match clues.attribute("type") {
+            Some("rows") => rows = Some(lines),
+            Some("columns") => columns = Some(lines),
+            _ => return Err(error(Some(clues), "<clues> type must be rows or columns")),
+        }
+    }
+    let rows = rows.ok_or_else(|| error(Some(puzzle), "no row clues"))?;
+    let columns = columns.ok_or_else(|| error(Some(puzzle), "no column clues"))?;
+
+    let goal = children(puzzle, "solution")
+        .find(|solution| solution.attribute("type").unwrap_or("goal") == "goal")
+        .and_then(|solution| children(solution, "image").next())
+        .map(|image| -> Result<Vec<Cell>, ParseError> {
+            let mut cells = Vec::new();
+            for code in image.text().unwrap_or("").chars() {
+                if code == '|' || code.is_whitespace() {
+                    continue;
+                }
+                let color = colors
+                    .iter()
+                    .find(|color| color.code == code)
+                    .ok_or_else(|| error(Some(image), &format!("unknown color \"{}\"", code)))?;
+                cells.push(if color.name == background_name {
+                    Cell::Empty
+                } else {
+                    Cell::Filled(code_of(image, &color.name)?)
+                });
+            }
+            Ok(cells)
+        })
+        .transpose()?;
+
+    let text = |name: &str| {
+        children(puzzle, name)
+            .next()
+            .and_then(|node| node.text())
+            .map(|text| text.trim().to_string())
+    };
+    let definitions = if is_colored {
+        let definitions = filled_colors
+            .iter()
+            .map(|color| ColorDefinition {
+                code: ColorCode(color.code),
+                color: color.color,
+            })
+            .collect();
+        Some(definitions)
+    } else {
+        None
+    };
+    let file = NonogramFile {
+        width: columns.len() as u64,
+        height: rows.len() as u64,
+        rows,
+        columns,
+        goal,
+        catalogue: text("id"),
+        title: text("title"),
+        by: text("author"),
+        copyright: text("copyright"),
+        license: None,
+        colors: definitions,
+    };
+    file.validate()
+        .map_err(|e| error(Some(puzzle), &e.to_string()))?;
+    Ok(file)
+}
+
+/// Write `file` in the webpbn XML format.
+pub fn to_webpbn_string(file: &NonogramFile) -> String {
+    let mut lines = vec![
+        r#"<?xml version="1.0"?>"#.to_string(),
+        r#"<!DOCTYPE pbn SYSTEM "https://webpbn.com/pbn-0.3.dtd">"#.to_string(),
+        "<puzzleset>".to_string(),
+    ];
+    let definitions = file.colors.as_ref().filter(|colors| !colors.is_empty());
+    let default_name = match definitions {
+        Some(colors) => colors[0].code.0.to_string(),
+        None => "black".to_string(),
+    };
+    lines.push(format!(
+        r#"<puzzle type="grid" defaultcolor="{}">"#,
+        escape(&default_name)
+    ));
+    let elements = [
+        ("id", &file.catalogue),
+        ("title", &file.title),
+        ("author", &file.by),
+        ("copyright", &file.copyright),
+    ];
+    for (name, value) in elements.iter() {
+        if let Some(value) = value {
+            lines.push(format!("<{}>{}</{}>", name, escape(value), name));
+        }
+    }
+    lines.push(format!(
+        r#"<color name="white" char="{}">fff</color>"#,
+        BACKGROUND_CODE
+    ));
+    match definitions {
+        Some(colors) => {
+            for definition in colors {
+                let Color { red, green, blue } = definition.color;
+                lines.push(format!(
+                    r#"<color name="{}" char="{}">{:02x}{:02x}{:02x}</color>"#,
+                    escape(&definition.code.0.to_string()),
+                    escape(&definition.code.0.to_string()),
+                    red,
+                    green,
+                    blue
+                ));
+            }
+        }
+        None => lines.push(format!(
+            r#"<color name="black" char="{}">000</color>"#,
+            FILLED_CODE
+        )),
+    }
+    for (kind, hints) in &[("columns", &file.columns), ("rows", &file.rows)] {
+        lines.push(format!(r#"<clues type="{}">"#, kind));
+        for line in hints.iter() {
+            let counts: String = line
+                .iter()
+                .map(|hint| match &hint.color {
+                    Some(ColorCode(code)) if definitions.is_some() => format!(
+                        r#"<count color="{}">{}</count>"#,
+                        escape(&code.to_string()),
+                        hint.length
+                    ),
+                    _ => format!("<count>{}</count>", hint.length),
+                })
+                .collect();
+            lines.push(format!("<line>{}</line>", counts));
+        }
+        lines.push("</clues>".to_string());
+    }
+    if let Some(goal) = &file.goal {
+        lines.push(r#"<solution type="goal">"#.to_string());
+        lines.push("<image>".to_string());
+        let width = (file.width as usize).max(1);
+        for row in goal.chunks(width) {
+            let codes: String = row
+                .iter()
+                .map(|cell| match cell {
+                    Cell::Empty => BACKGROUND_CODE,
+                    Cell::Filled(Some(ColorCode(code))) if definitions.is_some() => *code,
+                    Cell::Filled(_) => FILLED_CODE,
+                })
+                .collect();
+            lines.push(format!("|{}|", escape(&codes)));
+        }
+        lines.push("</image>".to_string());
+        lines.push("</solution>".to_string());
+    }
+    lines.push("</puzzle>".to_string());
+    lines.push("</puzzleset>".to_string());
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+fn children<'a, 'input: 'a>(
+    node: Node<'a, 'input>,
+    name: &'a str,
+) -> impl Iterator<Item = Node<'a, 'input>> + 'a {
+    node.children()
+        .filter(move |child| child.has_tag_name(name))
+}
+
+fn error(node: Option<Node>, message: &str) -> ParseError {
+    ParseError {
+        line: node.map(|node| node.document().text_pos_at(node.range().start).row as usize),
+        message: message.to_string(),
+    }
+}
+
+/// Parse a color written as 3 or 6 hex digits.
+fn parse_rgb(value: &str) -> Option<Color> {
+    let digits: Vec<u8> = value
+        .chars()
+        .map(|c| c.to_digit(16).map(|digit| digit as u8))
+        .collect::<Option<_>>()?;
+    let channel = |high: u8, low: u8| high * 16 + low;
+    match digits[..] {
+        [r, g, b] => Some(Color {
+            red: channel(r, r),
+            green: channel(g, g),
+            blue: channel(b, b),
+        }),
+        [r1, r2, g1, g2, b1, b2] => Some(Color {
+            red: channel(r1, r2),
+            green: channel(g1, g2),
+            blue: channel(b1, b2),
+        }),
+        _ => None,
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,134 @@
+use nonogram::nonogram_file::{Cell, ColorCode, Hint};
+use nonogram::Tile;
+use nonogram_parser::parser::{self, parse_non_with_warnings};
+use nonogram_parser::{parse, parse_non, Format};
+
+const TINY: &str = r#"
+catalogue "test #1"
+title "Tiny"
+width 3
+height 2
+
+rows
+3
+1,1
+
+columns
+2
+1
+2
+
+goal "111101"
+"#;
+
+fn hint(length: u64) -> Hint {
+    Hint {
+        length,
+        color: None,
+    }
+}
+
+#[test]
+fn tiny_file_is_parsed() {
+    let file = parse_non(TINY).unwrap();
+    assert_eq!(file.catalogue, Some("test #1".to_string()));
+    assert_eq!(file.title, Some("Tiny".to_string()));
+    assert_eq!((file.width, file.height), (3, 2));
+    assert_eq!(file.rows, vec![vec![hint(3)], vec![hint(1), hint(1)]]);
+    assert_eq!(
+        file.columns,
+        vec![vec![hint(2)], vec![hint(1)], vec![hint(2)]]
+    );
+    assert_eq!(file.goal.as_ref().map(Vec::len), Some(6));
+    assert_eq!(file.goal.as_ref().unwrap()[4], Cell::Empty);
+}
+
+#[test]
+fn file_converts_to_nonogram() {
+    let file = parse_non(TINY).unwrap();
+    let non = file.to_nonogram();
+    assert_eq!(non.row_clues(), &[vec![3], vec![1, 1]][..]);
+    assert_eq!(non.column_clues(), &[vec![2], vec![1], vec![2]][..]);
+    let goal = file.goal_tiles().unwrap();
+    assert_eq!(goal[1], vec![Tile::Filled, Tile::NotFilled, Tile::Filled]);
+}
+
+#[test]
+fn zero_means_no_hints() {
+    let input = "width 2\nheight 1\nrows\n0\ncolumns\n0\n0\n";
+    let file = parse_non(input).unwrap();
+    assert_eq!(file.rows, vec![Vec::new()]);
+    assert_eq!(file.columns, vec![Vec::new(), Vec::new()]);
+}
+
+#[test]
+fn unknown_keys_are_warnings() {
+    let input = "width 1\nheight 1\nfavourite \"tea\"\nrows\n1\ncolumns\n1\n";
+    let (_, warnings) = parse_non_with_warnings(input).unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].line, 3);
+}
+
+#[test]
+fn errors_report_their_line() {
+    let input = "width 2\nheight 1\nrows\n1,x\ncolumns\n1\n1\n";
+    let error = parse_non(input).unwrap_err();
+    assert_eq!(error.line, Some(4));
+}
+
+#[test]
+fn rows_before_height_are_an_error() {
+    let input = "width 1\nrows\n1\nheight 1\ncolumns\n1\n";
+    assert!(parse_non(input).is_err());
+}
+
+#[test]
+fn missing_columns_are_an_error() {
+    let error = parse_non("width 1\nheight 1\nrows\n1\n").unwrap_err();
+    assert_eq!(error.line, None);
+}
+
+#[test]
+fn colored_hints_and_goal_are_parsed() {
+    let input = "width 2\nheight 1\ncolor a ff0000\ncolor b #00ff00\n\
+                 rows\n1a,1b\ncolumns\n1a\n1b\ngoal ab\n";
+    let file = parse_non(input).unwrap();
+    assert_eq!(file.rows[0][1].color, Some(ColorCode('b')));
+    assert_eq!(file.colors.as_ref().map(Vec::len), Some(2));
+    assert_eq!(file.colors.as_ref().unwrap()[1].color.green, 255);
+    assert_eq!(file.goal.unwrap()[0], Cell::Filled(Some(ColorCode('a'))));
+}
+
+#[test]
+fn non_output_round_trips() {
+    let file = parse_non(TINY).unwrap();
+    assert_eq!(parse_non(&parser::to_non_string(&file)).unwrap(), file);
+}
+
+#[test]
+fn json_round_trips() {
+    let file = parse_non(TINY).unwrap();
+    let json = nonogram_parser::format::to_string(&file, Format::Json);
+    assert_eq!(parse(&json, Format::Json).unwrap(), file);
+}
+
+#[test]
+fn json_that_does_not_match_its_dimensions_is_an_error() {
+    let file = parse_non(TINY).unwrap();
+    let json = nonogram_parser::format::to_string(&file, Format::Json);
+    let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    value["height"] = serde_json::json!(file.height - 1);
+    assert!(parse(&value.to_string(), Format::Json).is_err());
+
+    let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    value["goal"].as_array_mut().unwrap().pop();
+    assert!(parse(&value.to_string(), Format::Json).is_err());
+}
+
+#[test]
+fn format_is_chosen_by_extension() {
+    use std::path::Path;
+    assert_eq!(Format::from_path(Path::new("a/b.non")), Some(Format::Non));
+    assert_eq!(Format::from_path(Path::new("b.JSON")), Some(Format::Json));
+    assert_eq!(Format::from_path(Path::new("b.txt")), None);
+}
@@ -0,0 +1,134 @@
+use nonogram::nonogram_file::{Cell, ColorCode};
+use nonogram_parser::parse_non;
+use nonogram_parser::webpbn::{parse_webpbn, to_webpbn_string};
+
+const MONOCHROME: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE pbn SYSTEM "https://webpbn.com/pbn-0.3.dtd">
+<puzzleset>
+<puzzle type="grid" defaultcolor="black">
+<source>webpbn.com</source>
+<id>#1</id>
+<title>Tiny</title>
+<author>someone</author>
+<color name="white" char=".">fff</color>
+<color name="black" char="X">000</color>
+<clues type="columns">
+<line><count>2</count></line>
+<line><count>1</count></line>
+<line><count>2</count></line>
+</clues>
+<clues type="rows">
+<line><count>3</count></line>
+<line><count>1</count><count>1</count></line>
+</clues>
+<solution type="goal">
+<image>
+|XXX|
+|X.X|
+</image>
+</solution>
+</puzzle>
+</puzzleset>
+"#;
+
+const COLORED: &str = r#"<?xml version="1.0"?>
+<puzzleset>
+<puzzle type="grid" defaultcolor="red">
+<color name="white" char=".">fff</color>
+<color name="red" char="r">f00</color>
+<color name="blue" char="b">0000ff</color>
+<clues type="columns">
+<line><count>1</count></line>
+<line><count color="blue">1</count></line>
+</clues>
+<clues type="rows">
+<line><count>1</count><count color="blue">1</count></line>
+</clues>
+<solution type="goal"><image>|rb|</image></solution>
+</puzzle>
+</puzzleset>
+"#;
+
+#[test]
+fn monochrome_puzzle_is_parsed() {
+    let file = parse_webpbn(MONOCHROME).unwrap();
+    let expected = parse_non(
+        "catalogue \"#1\"\ntitle \"Tiny\"\nby \"someone\"\nwidth 3\nheight 2\n\
+         rows\n3\n1,1\ncolumns\n2\n1\n2\ngoal 111101\n",
+    )
+    .unwrap();
+    assert_eq!(file, expected);
+}
+
+#[test]
+fn colored_puzzle_is_parsed() {
+    let file = parse_webpbn(COLORED).unwrap();
+    assert_eq!(file.rows[0][0].color, Some(ColorCode('r')));
+    assert_eq!(file.rows[0][1].color, Some(ColorCode('b')));
+    let colors = file.colors.as_ref().unwrap();
+    assert_eq!(colors.len(), 2);
+    assert_eq!(colors[0].color.red, 255);
+    assert_eq!(colors[1].color.blue, 255);
+    assert_eq!(
+        file.goal,
+        Some(vec![
+            Cell::Filled(Some(ColorCode('r'))),
+            Cell::Filled(Some(ColorCode('b')))
+        ])
+    );
+}
+
+#[test]
+fn black_and_white_puzzle_needs_no_color_elements() {
+    let input = MONOCHROME
+        .replace("<color name=\"white\" char=\".\">fff</color>\n", "")
+        .replace("<color name=\"black\" char=\"X\">000</color>\n", "");
+    assert_eq!(
+        parse_webpbn(&input).unwrap(),
+        parse_webpbn(MONOCHROME).unwrap()
+    );
+}
+
+#[test]
+fn color_elements_replace_the_predefined_colors() {
+    let input = MONOCHROME
+        .replace(
+            "<color name=\"black\" char=\"X\">000</color>",
+            "<color name=\"black\" char=\"#\">000</color>",
+        )
+        .replace("|XXX|\n|X.X|", "|###|\n|#.#|");
+    assert_eq!(
+        parse_webpbn(&input).unwrap(),
+        parse_webpbn(MONOCHROME).unwrap()
+    );
+    let stale = MONOCHROME.replace(
+        "<color name=\"black\" char=\"X\">000</color>",
+        "<color name=\"black\" char=\"#\">000</color>",
+    );
+    assert!(parse_webpbn(&stale).is_err());
+}
+
+#[test]
+fn written_xml_round_trips() {
+    for input in &[MONOCHROME, COLORED] {
+        let file = parse_webpbn(input).unwrap();
+        assert_eq!(parse_webpbn(&to_webpbn_string(&file)).unwrap(), file);
+    }
+}
+
+#[test]
+fn missing_clues_are_an_error() {
+    let input = r#"<puzzleset><puzzle><clues type="rows"/></puzzle></puzzleset>"#;
+    assert!(parse_webpbn(input).is_err());
+}
+
+#[test]
+fn short_goal_is_an_error() {
+    let input = MONOCHROME.replace("|X.X|", "|X.|");
+    assert!(parse_webpbn(&input).is_err());
+}
+
+#[test]
+fn malformed_xml_is_an_error() {
+    assert!(parse_webpbn("<puzzleset><puzzle>").is_err());
+}
@@ -0,0 +1,516 @@
+use nonogram::nonogram_file::NonogramFile;
+use nonogram::solver::Status;
+use nonogram::steps;
+use nonogram::{Line, LineState, MaybeTile, Nonogram, Tile};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// A puzzle the server can hand out.
+pub struct Puzzle {
+    /// The name of the puzzle in URLs: its file name without the extension.
+    pub id: String,
+    pub file: NonogramFile,
+    /// The solution, in the colors of the file's goal, if the puzzle has
+    /// exactly one.
+    pub solution: Option<Nonogram>,
+}
+
+/// A grid sent by the client to be checked or hinted at.
+#[derive(Deserialize)]
+struct Submission {
+    tiles: Vec<Vec<MaybeTile>>,
+}
+
+/// Answers requests for the puzzles in a directory.
+pub struct Api {
+    puzzles: Vec<Puzzle>,
+}
+
+impl Api {
+    pub fn new(puzzles: Vec<Puzzle>) -> Self {
+        Api { puzzles }
+    }
+
+    /// The status code and JSON body of the response to a request.
+    pub fn handle(&self, method: &str, url: &str, body: &str) -> (u16, Value) {
+        match self.route(method, url, body) {
+            Ok(value) => (200, value),
+            Err(response) => response,
+        }
+    }
+
+    fn route(&self, method: &str, url: &str, body: &str) -> Result<Value, (u16, Value)> {
+        let path = url.split('?').next().unwrap_or_default();
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        match (method, segments.as_slice()) {
+            ("GET", ["puzzles"]) => Ok(self.list()),
+            ("GET", ["puzzles", id]) => self.find(id).map(describe),
+            ("POST", ["puzzles", id, "verify"]) => {
+                let puzzle = self.find(id)?;
+                submitted(puzzle, body).map(|non| verify(puzzle, &non))
+            }
+            ("POST", ["puzzles", id, "hint"]) => {
+                let puzzle = self.find(id)?;
+                submitted(puzzle, body).map(|non| hint(puzzle, &non))
+            }
+            (_, ["puzzles"]) | (_, ["puzzles", _]) => Err(method_not_allowed(method)),
+            (_, ["puzzles", _, "verify"]) | (_, ["puzzles", _, "hint"]) => {
+                Err(method_not_allowed(method))
+            }
+            _ => Err(error(404, format!("no such path \"{}\"", path))),
+        }
+    }
+
+    fn find(&self, id: &str) -> Result<&Puzzle, (u16, Value)> {
+        self.puzzles
+            .iter()
+            .find(|puzzle| puzzle.id == id)
+            .ok_or_else(|| error(404, format!("no puzzle \"{}\"", id)))
+    }
+
+    fn list(&self) -> Value {
+        let puzzles: Vec<Value> = self
+            .puzzles
+            .iter()
+            .map(|puzzle| {
+                json!({
+                    "id": puzzle.id,
+                    "title": puzzle.file.title,
+                    "by": puzzle.file.by,
+                    "width": puzzle.file.width,
+                    "height": puzzle.file.height,
+                    "colored": puzzle.file.colors.is_some(),
+                })
+            })
+            .collect();
+        json!({ "puzzles": puzzles })
+    }
+}
+
+/// Everything about a puzzle except its goal.
+fn describe(puzzle: &Puzzle) -> Value {
+    let file = &puzzle.file;
+    json!({
+        "id": puzzle.id,
+        "title": file.title,
+        "by": file.by,
+        "copyright": file.copyright,
+        "license": file.license,
+        "catalogue": file.catalogue,
+        "width": file.width,
+        "height": file.height,
+        "rows": file.rows,
+        "columns": file.columns,
+        "colors": file.colors,
+    })
+}
+
+/// The puzzle with the tiles in the request body filled in.
+fn submitted(puzzle: &Puzzle, body: &str) -> Result<Nonogram, (u16, Value)> {
+    let submission: Submission =
+        serde_json::from_str(body).map_err(|e| error(400, format!("invalid submission: {}", e)))?;
+    let mut non = puzzle.file.to_nonogram();
+    let is_right_size = submission.tiles.len() == non.num_rows()
+        && submission
+            .tiles
+            .iter()
+            .all(|row| row.len() == non.num_cols());
+    if !is_right_size {
+        return Err(error(
+            400,
+            format!(
+                "tiles must be {} rows of {} tiles",
+                non.num_rows(),
+                non.num_cols()
+            ),
+        ));
+    }
+    for (row, tiles) in submission.tiles.iter().enumerate() {
+        for (column, &maybe_tile) in tiles.iter().enumerate() {
+            if let Some(tile) = maybe_tile {
+                non.set_tile(row, column, tile);
+            }
+        }
+    }
+    Ok(non)
+}
+
+/// Whether the grid solves the puzzle, and the state of each line.
+fn verify(puzzle: &Puzzle, non: &Nonogram) -> Value {
+    let line_states = |lines: Vec<Line>| -> Vec<&str> {
+        lines
+            .into_iter()
+            .map(|line| match non.line_state(line) {
+                LineState::Satisfied => "satisfied",
+                LineState::Possible => "possible",
+                LineState::Contradicted => "contradicted",
+            })
+            .collect()
+    };
+    json!({
+        "solved": is_solved(puzzle, non),
+        "complete": non.is_complete(),
+        "rows": line_states((0..non.num_rows()).map(Line::Row).collect()),
+        "columns": line_states((0..non.num_cols()).map(Line::Column).collect()),
+    })
+}
+
+/// Whether the grid matches the clues, in the colors they give. Where the
+/// clues give no colors, the filled tiles must still agree with the colors of
+/// the solution, if the puzzle has one.
+fn is_solved(puzzle: &Puzzle, non: &Nonogram) -> bool {
+    if non.is_colored() {
+        return non.is_correct_solution();
+    }
+    // Tiles of different colors would split runs that the clues don't
+    let mut filled = puzzle.file.to_nonogram();
+    for (row, column) in positions(non) {
+        if let Some(tile) = non.get_tile(row, column) {
+            let tile = match tile {
+                Tile::NotFilled => Tile::NotFilled,
+                _ => Tile::Filled,
+            };
+            filled.set_tile(row, column, tile);
+        }
+    }
+    let is_in_goal_colors = match &puzzle.solution {
+        Some(solution) => positions(non).all(|(row, column)| {
+            let tile = non.get_tile(row, column);
+            !tile.is_some_and(|tile| tile.is_filled())
+                || is_same_tile(tile, solution.get_tile(row, column))
+        }),
+        None => true,
+    };
+    filled.is_correct_solution() && is_in_goal_colors
+}
+
+/// The next thing the player could do: fix a tile that disagrees with the
+/// solution, make a deduction, or, when no deduction is possible, set a tile
+/// from the solution.
+fn hint(puzzle: &Puzzle, non: &Nonogram) -> Value {
+    let solution = puzzle.solution.as_ref();
+    if let Some(solution) = solution {
+        let mistake = positions(non).find(|&(row, column)| {
+            let tile = non.get_tile(row, column);
+            tile.is_some() && !is_same_tile(tile, solution.get_tile(row, column))
+        });
+        if let Some((row, column)) = mistake {
+            return json!({ "hint": "mistake", "row": row, "column": column });
+        }
+    }
+
+    let mut steps = steps::steps(non).with_probing();
+    if let Some(step) = steps.next() {
+        let changes: Vec<Value> = step
+            .changes
+            .iter()
+            .map(|&((row, column), tile)| {
+                // Steps only know which tiles are filled
+                let tile = solution
+                    .and_then(|solution| solution.get_tile(row, column))
+                    .unwrap_or(tile);
+                json!({ "row": row, "column": column, "tile": tile })
+            })
+            .collect();
+        return json!({
+            "hint": "step",
+            "line": line_json(step.line),
+            "clue": step.clue,
+            "technique": step.technique.to_string(),
+            "description": step.to_string(),
+            "changes": changes,
+        });
+    }
+    match steps.result() {
+        Some(Ok(Status::Solved)) => json!({ "hint": "solved" }),
+        Some(Err(contradiction)) => json!({
+            "hint": "contradiction",
+            "line": line_json(contradiction.line),
+            "description": contradiction.to_string(),
+        }),
+        _ => {
+            let reveal = solution.and_then(|solution| {
+                positions(non)
+                    .find(|&(row, column)| non.get_tile(row, column).is_none())
+                    .map(|(row, column)| (row, column, solution.get_tile(row, column)))
+            });
+            match reveal {
+                Some((row, column, tile)) => {
+                    json!({ "hint": "reveal", "row": row, "column": column, "tile": tile })
+                }
+                None => json!({ "hint": "stuck" }),
+            }
+        }
+    }
+}
+
+/// Whether two tiles agree, ignoring color where either has none.
+fn is_same_tile(tile: MaybeTile, other: MaybeTile) -> bool {
+    match (tile, other) {
+        (Some(Tile::FilledWithColor(code)), Some(Tile::FilledWithColor(other_code))) => {
+            code == other_code
+        }
+        (Some(tile), Some(other)) => tile.is_filled() == other.is_filled(),
+        _ => tile == other,
+    }
+}
+
+fn positions(non: &Nonogram) -> impl Iterator<Item = (usize, usize)> {
+    let num_cols = non.num_cols();
+    (0..non.num_rows()).flat_map(move |row| (0..num_cols).map(move |column| (row, column)))
+}
+
+fn line_json(line: Line) -> Value {
+    match line {
+        Line::Row(row) => json!({ "row": row }),
+        Line::Column(column) => json!({ "column": column }),
+    }
+}
+
+fn method_not_allowed(method: &str) -> (u16, Value) {
+    error(405, format!("method {} is not allowed here", method))
+}
+
+fn error(status: u16, message: String) -> (u16, Value) {
+    (status, json!({ "error": message }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nonogram::nonogram_file::{Cell, Color, ColorCode, ColorDefinition, Hint};
+
+    fn hint(length: u64, color: Option<char>) -> Hint {
+        Hint {
+            length,
+            color: color.map(ColorCode),
+        }
+    }
+
+    fn file(rows: Vec<Vec<Hint>>, columns: Vec<Vec<Hint>>, goal: Vec<Cell>) -> NonogramFile {
+        NonogramFile {
+            width: columns.len() as u64,
+            height: rows.len() as u64,
+            rows,
+            columns,
+            goal: Some(goal),
+            catalogue: None,
+            title: None,
+            by: None,
+            copyright: None,
+            license: None,
+            colors: None,
+        }
+    }
+
+    fn puzzle(id: &str, file: NonogramFile) -> Puzzle {
+        let mut solutions = file.solve(2);
+        let solution = match solutions.len() {
+            1 => solutions.pop(),
+            _ => None,
+        };
+        Puzzle {
+            id: id.to_string(),
+            file,
+            solution,
+        }
+    }
+
+    fn colors() -> Option<Vec<ColorDefinition>> {
+        let define = |code, red, green, blue| ColorDefinition {
+            code: ColorCode(code),
+            color: Color { red, green, blue },
+        };
+        Some(vec![define('a', 255, 0, 0), define('b', 0, 0, 255)])
+    }
+
+    /// The puzzles:
+    ///
+    /// - `line`: one row of two filled tiles
+    /// - `colored`: one row of an `a` tile and a `b` tile, with colored clues
+    /// - `painted`: the same goal with monochrome clues
+    /// - `diagonal`: two rows of two tiles, with two solutions
+    /// - `guessed`: `diagonal` with one of its solutions to reveal
+    fn get_api() -> Api {
+        let colored_goal = || {
+            vec![
+                Cell::Filled(Some(ColorCode('a'))),
+                Cell::Filled(Some(ColorCode('b'))),
+            ]
+        };
+        let mut colored = file(
+            vec![vec![hint(1, Some('a')), hint(1, Some('b'))]],
+            vec![vec![hint(1, Some('a'))], vec![hint(1, Some('b'))]],
+            colored_goal(),
+        );
+        colored.colors = colors();
+        let mut painted = file(
+            vec![vec![hint(2, None)]],
+            vec![vec![hint(1, None)], vec![hint(1, None)]],
+            colored_goal(),
+        );
+        painted.colors = colors();
+        let diagonal = || {
+            file(
+                vec![vec![hint(1, None)], vec![hint(1, None)]],
+                vec![vec![hint(1, None)], vec![hint(1, None)]],
+                vec![
+                    Cell::Filled(None),
+                    Cell::Empty,
+                    Cell::Empty,
+                    Cell::Filled(None),
+                ],
+            )
+        };
+        let mut guessed = puzzle("guessed", diagonal());
+        guessed.solution = guessed.file.solve(1).pop();
+        Api::new(vec![
+            puzzle(
+                "line",
+                file(
+                    vec![vec![hint(2, None)]],
+                    vec![vec![hint(1, None)], vec![hint(1, None)]],
+                    vec![Cell::Filled(None), Cell::Filled(None)],
+                ),
+            ),
+            puzzle("colored", colored),
+            puzzle("painted", painted),
+            puzzle("diagonal", diagonal()),
+            guessed,
+        ])
+    }
+
+    fn post(api: &Api, url: &str, tiles: Value) -> (u16, Value) {
+        api.handle("POST", url, &json!({ "tiles": tiles }).to_string())
+    }
+
+    #[test]
+    fn list_and_fetch_puzzles() {
+        let api = get_api();
+        let (status, value) = api.handle("GET", "/puzzles", "");
+        assert_eq!(status, 200);
+        assert_eq!(value["puzzles"].as_array().unwrap().len(), 5);
+        assert_eq!(value["puzzles"][1]["id"], "colored");
+        assert_eq!(value["puzzles"][1]["colored"], true);
+
+        let (status, value) = api.handle("GET", "/puzzles/line?seed=1", "");
+        assert_eq!(status, 200);
+        assert_eq!(value["width"], 2);
+        assert_eq!(value["rows"], json!([[{ "length": 2, "color": null }]]));
+        assert!(value.get("goal").is_none());
+    }
+
+    #[test]
+    fn unknown_paths_and_methods_are_errors() {
+        let api = get_api();
+        assert_eq!(api.handle("GET", "/puzzles/missing", "").0, 404);
+        assert_eq!(api.handle("POST", "/puzzles/missing/verify", "").0, 404);
+        assert_eq!(api.handle("GET", "/solutions", "").0, 404);
+        assert_eq!(api.handle("DELETE", "/puzzles/line", "").0, 405);
+        assert_eq!(api.handle("GET", "/puzzles/line/hint", "").0, 405);
+    }
+
+    #[test]
+    fn submissions_must_be_the_size_of_the_puzzle() {
+        let api = get_api();
+        let (status, value) = post(&api, "/puzzles/line/verify", json!([[null]]));
+        assert_eq!(status, 400);
+        assert_eq!(value["error"], "tiles must be 1 rows of 2 tiles");
+        let (status, _) = post(&api, "/puzzles/line/hint", json!([[null, null], []]));
+        assert_eq!(status, 400);
+        assert_eq!(api.handle("POST", "/puzzles/line/verify", "{").0, 400);
+    }
+
+    #[test]
+    fn verify_monochrome() {
+        let api = get_api();
+        let (status, value) = post(&api, "/puzzles/line/verify", json!([["filled", "filled"]]));
+        assert_eq!(status, 200);
+        assert_eq!(value["solved"], true);
+        assert_eq!(value["complete"], true);
+        assert_eq!(value["rows"], json!(["satisfied"]));
+
+        let (_, value) = post(&api, "/puzzles/line/verify", json!([["filled", null]]));
+        assert_eq!(value["solved"], false);
+        assert_eq!(value["complete"], false);
+        assert_eq!(value["rows"], json!(["possible"]));
+        assert_eq!(value["columns"], json!(["satisfied", "possible"]));
+    }
+
+    #[test]
+    fn verify_colored() {
+        let api = get_api();
+        let a = json!({ "filled_with_color": "a" });
+        let b = json!({ "filled_with_color": "b" });
+        let (_, value) = post(&api, "/puzzles/colored/verify", json!([[a, b]]));
+        assert_eq!(value["solved"], true);
+        let (_, value) = post(&api, "/puzzles/colored/verify", json!([[b, a]]));
+        assert_eq!(value["solved"], false);
+        assert_eq!(value["rows"], json!(["contradicted"]));
+        let (_, value) = post(
+            &api,
+            "/puzzles/colored/verify",
+            json!([["filled", "filled"]]),
+        );
+        assert_eq!(value["solved"], false);
+
+        // Monochrome clues still need the colors of the goal
+        let (_, value) = post(&api, "/puzzles/painted/verify", json!([[a, b]]));
+        assert_eq!(value["solved"], true);
+        let (_, value) = post(&api, "/puzzles/painted/verify", json!([[b, a]]));
+        assert_eq!(value["solved"], false);
+    }
+
+    #[test]
+    fn hint_mistake() {
+        let api = get_api();
+        let (_, value) = post(
+            &api,
+            "/puzzles/line/hint",
+            json!([["filled", "not_filled"]]),
+        );
+        assert_eq!(value, json!({ "hint": "mistake", "row": 0, "column": 1 }));
+    }
+
+    #[test]
+    fn hint_step() {
+        let api = get_api();
+        let (_, value) = post(&api, "/puzzles/colored/hint", json!([[null, null]]));
+        assert_eq!(value["hint"], "step");
+        let change = &value["changes"][0];
+        assert_eq!(change["tile"], json!({ "filled_with_color": "a" }));
+    }
+
+    #[test]
+    fn hint_reveal() {
+        let api = get_api();
+        let empty = json!([[null, null], [null, null]]);
+        let (_, value) = post(&api, "/puzzles/guessed/hint", empty);
+        assert_eq!(value["hint"], "reveal");
+        assert_eq!((&value["row"], &value["column"]), (&json!(0), &json!(0)));
+    }
+
+    #[test]
+    fn hint_stuck_without_a_solution() {
+        let api = get_api();
+        let empty = json!([[null, null], [null, null]]);
+        let (_, value) = post(&api, "/puzzles/diagonal/hint", empty);
+        assert_eq!(value, json!({ "hint": "stuck" }));
+    }
+
+    #[test]
+    fn hint_contradiction() {
+        let api = get_api();
+        let tiles = json!([["filled", "filled"], [null, null]]);
+        let (_, value) = post(&api, "/puzzles/diagonal/hint", tiles);
+        assert_eq!(value["hint"], "contradiction");
+        assert_eq!(value["line"], json!({ "row": 0 }));
+    }
+
+    #[test]
+    fn hint_solved() {
+        let api = get_api();
+        let (_, value) = post(&api, "/puzzles/line/hint", json!([["filled", "filled"]]));
+        assert_eq!(value, json!({ "hint": "solved" }));
+    }
+}
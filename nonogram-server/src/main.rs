@@ -0,0 +1,192 @@
+mod api;
+
+use crate::api::{Api, Puzzle};
+use nonogram_parser::Format;
+use std::env;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+const USAGE: &str = "\
+Usage:
+    nonogram-server [options] DIRECTORY
+
+Serves every puzzle file (.non, .json or .xml) in DIRECTORY as JSON on
+http://127.0.0.1:PORT, for a web frontend to play them. Only connections from
+this machine are accepted.
+
+Endpoints:
+    GET  /puzzles              List the puzzles
+    GET  /puzzles/ID           The clues of a puzzle, without its solution
+    POST /puzzles/ID/verify    Check a grid: {\"tiles\": [[null, \"filled\", ...], ...]}
+    POST /puzzles/ID/hint      Suggest the next move for a grid
+
+Options:
+    --port PORT            The port to listen on (default 8080)
+    --allow-origin ORIGIN  Let pages from ORIGIN make requests, such as
+                           http://localhost:3000
+    -h, --help             Print this message";
+
+/// The most a request body can be, which is plenty for a grid of tiles.
+const MAX_BODY_LEN: u64 = 1 << 20;
+
+pub fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut port: u16 = 8080;
+    let mut allowed_origin = None;
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            "--port" | "--allow-origin" => {
+                let value = match args.next() {
+                    Some(value) => value.as_str(),
+                    None => usage_error(&format!("{} needs a value", arg)),
+                };
+                if arg == "--port" {
+                    port = match value.parse() {
+                        Ok(port) => port,
+                        Err(_) => usage_error(&format!("invalid port \"{}\"", value)),
+                    };
+                } else {
+                    allowed_origin = Some(value.to_string());
+                }
+            }
+            _ if arg.starts_with("--") => usage_error(&format!("unknown option \"{}\"", arg)),
+            _ => paths.push(arg.as_str()),
+        }
+    }
+    let directory = match paths.as_slice() {
+        [directory] => Path::new(directory),
+        _ => usage_error("needs exactly one directory"),
+    };
+
+    let puzzles = match load_puzzles(directory) {
+        Ok(puzzles) => puzzles,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            process::exit(1);
+        }
+    };
+    if puzzles.is_empty() {
+        eprintln!("error: {}: no puzzle files", directory.display());
+        process::exit(1);
+    }
+    let num_puzzles = puzzles.len();
+    let api = Api::new(puzzles);
+
+    let server = match Server::http(("127.0.0.1", port)) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("error: can't listen on port {}: {}", port, e);
+            process::exit(1);
+        }
+    };
+    eprintln!(
+        "Serving {} puzzles on http://127.0.0.1:{}/puzzles",
+        num_puzzles, port
+    );
+    for request in server.incoming_requests() {
+        respond(&api, request, allowed_origin.as_deref());
+    }
+}
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}\n\n{}", message, USAGE);
+    process::exit(2);
+}
+
+fn respond(api: &Api, mut request: Request, allowed_origin: Option<&str>) {
+    let method = request.method().clone();
+    let url = request.url().to_string();
+    let (status, body) = if method == Method::Options {
+        // A browser asking whether a page from another origin may post JSON
+        (204, String::new())
+    } else {
+        let mut body = String::new();
+        let read = request
+            .as_reader()
+            .take(MAX_BODY_LEN)
+            .read_to_string(&mut body);
+        let (status, value) = match read {
+            Ok(_) => api.handle(method.as_str(), &url, &body),
+            Err(e) => (
+                400,
+                serde_json::json!({ "error": format!("can't read body: {}", e) }),
+            ),
+        };
+        (status, value.to_string())
+    };
+    eprintln!("{} {} {}", method, url, status);
+
+    let mut response = Response::from_string(body)
+        .with_status_code(status)
+        .with_header(header("Content-Type", "application/json"));
+    if let Some(origin) = allowed_origin {
+        response = response
+            .with_header(header("Access-Control-Allow-Origin", origin))
+            .with_header(header("Access-Control-Allow-Methods", "GET, POST"))
+            .with_header(header("Access-Control-Allow-Headers", "Content-Type"));
+    }
+    if let Err(e) = request.respond(response) {
+        eprintln!("warning: couldn't send response: {}", e);
+    }
+}
+
+fn header(field: &str, value: &str) -> Header {
+    Header::from_bytes(field.as_bytes(), value.as_bytes()).expect("header is valid ASCII")
+}
+
+/// Every puzzle file in `directory`, in order of file name, and its solution
+/// if it has exactly one. Files that fail to load are skipped with a warning.
+fn load_puzzles(directory: &Path) -> Result<Vec<Puzzle>, String> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(directory)
+        .map_err(|e| format!("{}: {}", directory.display(), e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| Format::from_path(path).is_some())
+        .collect();
+    paths.sort();
+
+    let mut puzzles: Vec<Puzzle> = Vec::with_capacity(paths.len());
+    for path in &paths {
+        let file = match nonogram_parser::load(path) {
+            Ok(file) => file,
+            Err(e) => {
+                eprintln!("warning: {}: skipped: {}", path.display(), e);
+                continue;
+            }
+        };
+        let id = path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        if puzzles.iter().any(|puzzle| puzzle.id == id) {
+            eprintln!(
+                "warning: {}: skipped: another file is named {}",
+                path.display(),
+                id
+            );
+            continue;
+        }
+        let mut solutions = file.solve(2);
+        let solution = match solutions.len() {
+            1 => solutions.pop(),
+            _ => None,
+        };
+        if solution.is_none() {
+            eprintln!(
+                "warning: {}: no unique solution, so hints can't point out mistakes",
+                path.display()
+            );
+        }
+        puzzles.push(Puzzle { id, file, solution });
+    }
+    Ok(puzzles)
+}
@@ -0,0 +1,242 @@
+use nonogram::formatter::ColorMode;
+use nonogram::nonogram_file::NonogramFile;
+use nonogram::{Formatter, Nonogram};
+use nonogram_parser::Format;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{Duration, Instant};
+
+const USAGE: &str = "\
+Usage:
+    nonogram-solve [--color MODE] FILE...
+    nonogram-solve --bench DIRECTORY
+
+Solves each puzzle file (.non, .json or .xml) and prints its solution,
+whether the solution is unique, and how long solving took.
+
+Options:
+    --bench DIRECTORY  Solve every puzzle file in DIRECTORY and summarize the
+                       results and timings
+    --color MODE       How to draw the colors of a puzzle's goal: truecolor,
+                       256 or none. By default truecolor if $COLORTERM says
+                       the terminal supports it, 256 otherwise, and none if
+                       the output isn't a terminal
+    -h, --help         Print this message";
+
+pub fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let color_mode = match args.iter().position(|arg| arg == "--color") {
+        Some(index) if index + 1 < args.len() => {
+            let value = args.remove(index + 1);
+            args.remove(index);
+            parse_color_mode(&value)
+        }
+        Some(_) => Err("--color needs a value".to_string()),
+        None => Ok(default_color_mode()),
+    };
+    let color_mode = match color_mode {
+        Ok(color_mode) => color_mode,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            process::exit(1);
+        }
+    };
+    let succeeded = match args.first().map(String::as_str) {
+        None | Some("-h") | Some("--help") => {
+            println!("{}", USAGE);
+            true
+        }
+        Some("--bench") if args.len() == 2 => bench(Path::new(&args[1])),
+        Some("--bench") => {
+            eprintln!("--bench takes exactly one directory\n\n{}", USAGE);
+            false
+        }
+        Some(_) => solve_files(&args, color_mode),
+    };
+    if !succeeded {
+        process::exit(1);
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Outcome {
+    Unique,
+    Ambiguous,
+    Unsolvable,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Outcome::Unique => "unique",
+            Outcome::Ambiguous => "ambiguous",
+            Outcome::Unsolvable => "unsolvable",
+        };
+        f.pad(s)
+    }
+}
+
+struct Solved {
+    outcome: Outcome,
+    /// A solution, if there is one.
+    solution: Option<Nonogram>,
+    time: Duration,
+}
+
+/// Solve the file, looking for a second solution to tell whether the first is
+/// unique.
+fn solve(file: &NonogramFile) -> Solved {
+    let start = Instant::now();
+    let mut solutions = file.solve(2);
+    let outcome = match solutions.len() {
+        0 => Outcome::Unsolvable,
+        1 => Outcome::Unique,
+        _ => Outcome::Ambiguous,
+    };
+    solutions.truncate(1);
+    Solved {
+        outcome,
+        solution: solutions.pop(),
+        time: start.elapsed(),
+    }
+}
+
+fn parse_color_mode(value: &str) -> Result<Option<ColorMode>, String> {
+    match value {
+        "truecolor" => Ok(Some(ColorMode::TrueColor)),
+        "256" => Ok(Some(ColorMode::Ansi256)),
+        "none" => Ok(None),
+        _ => Err(format!(
+            "unknown color mode \"{}\" (expected truecolor, 256 or none)",
+            value
+        )),
+    }
+}
+
+fn default_color_mode() -> Option<ColorMode> {
+    if !io::stdout().is_terminal() {
+        return None;
+    }
+    match env::var("COLORTERM").as_deref() {
+        Ok("truecolor") | Ok("24bit") => Some(ColorMode::TrueColor),
+        _ => Some(ColorMode::Ansi256),
+    }
+}
+
+fn solve_files(paths: &[String], color_mode: Option<ColorMode>) -> bool {
+    let mut succeeded = true;
+    for (index, path) in paths.iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+        let file = match nonogram_parser::load(Path::new(path)) {
+            Ok(file) => file,
+            Err(e) => {
+                eprintln!("{}", e);
+                succeeded = false;
+                continue;
+            }
+        };
+        match &file.title {
+            Some(title) => println!("{}: \"{}\" ({}x{})", path, title, file.width, file.height),
+            None => println!("{} ({}x{})", path, file.width, file.height),
+        }
+        let formatter = Formatter::default().with_guides(5);
+        let formatter = match (color_mode, &file.colors) {
+            (Some(mode), Some(colors)) => formatter.with_colors(mode, colors),
+            _ => formatter,
+        };
+        let solved = solve(&file);
+        if let Some(solution) = &solved.solution {
+            for line in formatter.get_lines(solution) {
+                println!("{}", line);
+            }
+        }
+        let description = match solved.outcome {
+            Outcome::Unique => "unique solution",
+            Outcome::Ambiguous => "more than one solution, showing the first",
+            Outcome::Unsolvable => "no solution",
+        };
+        println!("{}, solved in {}", description, format_time(solved.time));
+    }
+    succeeded
+}
+
+fn bench(directory: &Path) -> bool {
+    let mut paths: Vec<PathBuf> = match fs::read_dir(directory) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| Format::from_path(path).is_some())
+            .collect(),
+        Err(e) => {
+            eprintln!("{}: {}", directory.display(), e);
+            return false;
+        }
+    };
+    paths.sort();
+
+    let mut times = Vec::with_capacity(paths.len());
+    let mut counts = [0; 3];
+    let mut failed = 0;
+    for path in &paths {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let file = match nonogram_parser::load(path) {
+            Ok(file) => file,
+            Err(e) => {
+                println!("{:>12}  {:<10}  {} ({})", "-", "failed", name, e);
+                failed += 1;
+                continue;
+            }
+        };
+        let solved = solve(&file);
+        println!(
+            "{:>12}  {:<10}  {}",
+            format_time(solved.time),
+            solved.outcome,
+            name
+        );
+        counts[solved.outcome as usize] += 1;
+        times.push(solved.time);
+    }
+
+    println!();
+    println!(
+        "{} puzzles: {} unique, {} ambiguous, {} unsolvable, {} failed to load",
+        paths.len(),
+        counts[Outcome::Unique as usize],
+        counts[Outcome::Ambiguous as usize],
+        counts[Outcome::Unsolvable as usize],
+        failed
+    );
+    if !times.is_empty() {
+        times.sort();
+        let total: Duration = times.iter().sum();
+        println!(
+            "total {}, mean {}",
+            format_time(total),
+            format_time(total / times.len() as u32)
+        );
+        println!(
+            "p50 {}, p90 {}, p99 {}, max {}",
+            format_time(percentile(&times, 50)),
+            format_time(percentile(&times, 90)),
+            format_time(percentile(&times, 99)),
+            format_time(times[times.len() - 1])
+        );
+    }
+    failed == 0
+}
+
+/// The nearest-rank percentile of `sorted`, which must not be empty.
+fn percentile(sorted: &[Duration], percent: usize) -> Duration {
+    let rank = ((percent * sorted.len() + 99) / 100).max(1);
+    sorted[rank - 1]
+}
+
+fn format_time(time: Duration) -> String {
+    format!("{:.3} ms", time.as_secs_f64() * 1000.0)
+}
@@ -0,0 +1,215 @@
+//! The config file read at startup
+//!
+//! The config file is TOML. By default it is read from
+//! `$XDG_CONFIG_HOME/nonogram-term/config.toml`, or
+//! `~/.config/nonogram-term/config.toml`, and it is fine for it not to exist.
+//!
+//! ```toml
+//! # The key bindings to start from: default, vim or wasd
+//! preset = "vim"
+//! # How the grid is drawn: unicode, ascii, blocks, high-contrast or colorblind
+//! theme = "ascii"
+//! # A guide line every this many rows and columns, or 0 for none
+//! guides = 5
+//! # Number the rows and columns
+//! rulers = true
+//! # Highlight the whole focused row and column
+//! crosshair = true
+//!
+//! # Keys for individual actions, replacing the preset's
+//! [keys]
+//! fill = "space"
+//! cross = ["x", "delete"]
+//!
+//! # Glyphs replacing the theme's
+//! [glyphs]
+//! filled = "@@"
+//! unknown = ".."
+//! cell_width = 2
+//! cell_gap = 0
+//! ```
+
+use crate::keys::{KeyMap, Preset};
+use crate::theme::Theme;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub preset: Option<String>,
+    pub keys: BTreeMap<String, Keys>,
+    pub theme: Option<String>,
+    pub glyphs: Glyphs,
+    pub guides: Option<usize>,
+    pub rulers: bool,
+    pub crosshair: bool,
+}
+
+/// One key or a list of keys.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Keys {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// Changes to the theme's glyphs and their width.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Glyphs {
+    pub filled: Option<String>,
+    pub not_filled: Option<String>,
+    pub unknown: Option<String>,
+    pub dot: Option<String>,
+    pub maybe: Option<String>,
+    pub top_divider: Option<char>,
+    pub side_divider: Option<char>,
+    pub corner_divider: Option<char>,
+    pub cross_divider: Option<char>,
+    pub cell_width: Option<usize>,
+    pub cell_gap: Option<usize>,
+}
+
+impl Config {
+    /// Read the config file at `path`, or at the default path if there is
+    /// none. Only a missing default config file is not an error.
+    pub fn load(path: Option<&Path>) -> Result<Self, String> {
+        let (path, is_default) = match path {
+            Some(path) => (path.to_owned(), false),
+            None => match default_path() {
+                Some(path) => (path, true),
+                None => return Ok(Config::default()),
+            },
+        };
+        let input = match fs::read_to_string(&path) {
+            Ok(input) => input,
+            Err(_) if is_default && !path.exists() => return Ok(Config::default()),
+            Err(e) => return Err(format!("{}: {}", path.display(), e)),
+        };
+        toml::from_str(&input).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// The key map from the preset and the keys of this config. `preset`
+    /// replaces the config's preset if it is given.
+    pub fn key_map(&self, preset: Option<&str>) -> Result<KeyMap, String> {
+        let preset = match preset.or(self.preset.as_deref()) {
+            Some(name) => name.parse()?,
+            None => Preset::Default,
+        };
+        let overrides = self
+            .keys
+            .iter()
+            .map(|(action, keys)| {
+                let keys = match keys {
+                    Keys::One(key) => vec![key.clone()],
+                    Keys::Many(keys) => keys.clone(),
+                };
+                (action.clone(), keys)
+            })
+            .collect();
+        KeyMap::from_preset(preset).with_overrides(&overrides)
+    }
+
+    /// The theme with the glyphs of this config. `theme` replaces the config's
+    /// theme if it is given.
+    pub fn theme(&self, theme: Option<&str>) -> Result<Theme, String> {
+        let mut theme = match theme.or(self.theme.as_deref()) {
+            Some(name) => Theme::named(name)?,
+            None => Theme::default(),
+        };
+        let glyphs = &self.glyphs;
+        if let Some(filled) = &glyphs.filled {
+            theme.filled = filled.clone();
+        }
+        if let Some(not_filled) = &glyphs.not_filled {
+            theme.not_filled = not_filled.clone();
+        }
+        if let Some(unknown) = &glyphs.unknown {
+            theme.unknown = unknown.clone();
+        }
+        if let Some(dot) = &glyphs.dot {
+            theme.dot = dot.clone();
+        }
+        if let Some(maybe) = &glyphs.maybe {
+            theme.maybe = maybe.clone();
+        }
+        theme.top_divider = glyphs.top_divider.unwrap_or(theme.top_divider);
+        theme.side_divider = glyphs.side_divider.unwrap_or(theme.side_divider);
+        theme.corner_divider = glyphs.corner_divider.unwrap_or(theme.corner_divider);
+        theme.cross_divider = glyphs.cross_divider.unwrap_or(theme.cross_divider);
+        theme.cell_gap = glyphs.cell_gap.unwrap_or(theme.cell_gap);
+
+        let tile_glyphs = [
+            &theme.filled,
+            &theme.not_filled,
+            &theme.unknown,
+            &theme.dot,
+            &theme.maybe,
+        ];
+        let widest = tile_glyphs
+            .iter()
+            .map(|glyph| glyph.chars().count())
+            .max()
+            .unwrap();
+        match glyphs.cell_width {
+            Some(0) => return Err("cell_width must be at least 1".to_string()),
+            Some(width) if width < widest => {
+                return Err(format!(
+                    "the glyphs are wider than the cell_width of {}",
+                    width
+                ))
+            }
+            Some(width) => theme.cell_width = width,
+            None => theme.cell_width = theme.cell_width.max(widest),
+        }
+        if let Some(guides) = self.guides {
+            theme.guide_every = if guides == 0 { None } else { Some(guides) };
+        }
+        theme.rulers = self.rulers;
+        theme.crosshair = self.crosshair;
+        Ok(theme)
+    }
+}
+
+fn default_path() -> Option<PathBuf> {
+    let config_dir = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+    Some(config_dir.join("nonogram-term").join("config.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The example config in this module's documentation.
+    fn doc_example() -> String {
+        include_str!("config.rs")
+            .lines()
+            .filter_map(|line| line.strip_prefix("//!"))
+            .skip_while(|line| *line != " ```toml")
+            .skip(1)
+            .take_while(|line| *line != " ```")
+            .map(|line| line.trim_start())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn doc_example_loads() {
+        let config: Config = toml::from_str(&doc_example()).unwrap();
+        assert_eq!(config.preset.as_deref(), Some("vim"));
+        assert_eq!(config.theme.as_deref(), Some("ascii"));
+        assert_eq!(config.guides, Some(5));
+        assert!(config.rulers && config.crosshair);
+        assert_eq!(config.keys.len(), 2);
+        assert_eq!(config.glyphs.cell_width, Some(2));
+        config.key_map(None).unwrap();
+        config.theme(None).unwrap();
+    }
+}
@@ -0,0 +1,398 @@
+//! Key bindings for the game's actions
+//!
+//! Bindings start from a [Preset] and can be changed per action in the config
+//! file. Keys are written as a single character (`"z"`, `"$"`) or one of the
+//! names in [parse_key], like `"up"` or `"home"`.
+
+use cursive::event::{Event, Key};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    Fill,
+    Cross,
+    Clear,
+    ToggleAutoCross,
+    RowStart,
+    RowEnd,
+    ColumnStart,
+    ColumnEnd,
+    NextUnknown,
+    Dot,
+    Maybe,
+    GuessFill,
+    GuessCross,
+    NextPencil,
+    CommitGuesses,
+    DiscardGuesses,
+    AnimateSolver,
+    ReplayMoves,
+    Faster,
+    Slower,
+}
+
+impl Action {
+    pub const ALL: [Action; 24] = [
+        Action::Up,
+        Action::Down,
+        Action::Left,
+        Action::Right,
+        Action::Fill,
+        Action::Cross,
+        Action::Clear,
+        Action::ToggleAutoCross,
+        Action::RowStart,
+        Action::RowEnd,
+        Action::ColumnStart,
+        Action::ColumnEnd,
+        Action::NextUnknown,
+        Action::Dot,
+        Action::Maybe,
+        Action::GuessFill,
+        Action::GuessCross,
+        Action::NextPencil,
+        Action::CommitGuesses,
+        Action::DiscardGuesses,
+        Action::AnimateSolver,
+        Action::ReplayMoves,
+        Action::Faster,
+        Action::Slower,
+    ];
+
+    /// The name used for the action in the config file.
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Left => "left",
+            Action::Right => "right",
+            Action::Fill => "fill",
+            Action::Cross => "cross",
+            Action::Clear => "clear",
+            Action::ToggleAutoCross => "toggle_auto_cross",
+            Action::RowStart => "row_start",
+            Action::RowEnd => "row_end",
+            Action::ColumnStart => "column_start",
+            Action::ColumnEnd => "column_end",
+            Action::NextUnknown => "next_unknown",
+            Action::Dot => "dot",
+            Action::Maybe => "maybe",
+            Action::GuessFill => "guess_fill",
+            Action::GuessCross => "guess_cross",
+            Action::NextPencil => "next_pencil",
+            Action::CommitGuesses => "commit_guesses",
+            Action::DiscardGuesses => "discard_guesses",
+            Action::AnimateSolver => "animate_solver",
+            Action::ReplayMoves => "replay_moves",
+            Action::Faster => "faster",
+            Action::Slower => "slower",
+        }
+    }
+}
+
+impl FromStr for Action {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Action::ALL
+            .iter()
+            .cloned()
+            .find(|action| action.name() == s)
+            .ok_or_else(|| format!("unknown action \"{}\"", s))
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Preset {
+    /// Arrow keys to move, `z`, `x` and `c` to fill, cross and clear.
+    Default,
+    /// `hjkl` to move as well as the arrow keys, with vim's `0`, `$`, `g` and
+    /// `G` to jump.
+    Vim,
+    /// `wasd` to move, with the actions on the keys to the right of them.
+    Wasd,
+}
+
+impl FromStr for Preset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "default" => Ok(Preset::Default),
+            "vim" => Ok(Preset::Vim),
+            "wasd" => Ok(Preset::Wasd),
+            _ => Err(format!(
+                "unknown key preset \"{}\" (expected default, vim or wasd)",
+                s
+            )),
+        }
+    }
+}
+
+impl Preset {
+    fn bindings(self) -> Vec<(Action, &'static [&'static str])> {
+        match self {
+            Preset::Default => vec![
+                (Action::Up, &["up"]),
+                (Action::Down, &["down"]),
+                (Action::Left, &["left"]),
+                (Action::Right, &["right"]),
+                (Action::Fill, &["z"]),
+                (Action::Cross, &["x"]),
+                (Action::Clear, &["c"]),
+                (Action::ToggleAutoCross, &["a"]),
+                (Action::RowStart, &["home"]),
+                (Action::RowEnd, &["end"]),
+                (Action::ColumnStart, &["pageup"]),
+                (Action::ColumnEnd, &["pagedown"]),
+                (Action::NextUnknown, &["n"]),
+                (Action::Dot, &["."]),
+                (Action::Maybe, &["m"]),
+                (Action::GuessFill, &["Z"]),
+                (Action::GuessCross, &["X"]),
+                (Action::NextPencil, &["P"]),
+                (Action::CommitGuesses, &["C"]),
+                (Action::DiscardGuesses, &["D"]),
+                (Action::AnimateSolver, &["S"]),
+                (Action::ReplayMoves, &["R"]),
+                (Action::Faster, &["+"]),
+                (Action::Slower, &["-"]),
+            ],
+            Preset::Vim => vec![
+                (Action::Up, &["k", "up"]),
+                (Action::Down, &["j", "down"]),
+                (Action::Left, &["h", "left"]),
+                (Action::Right, &["l", "right"]),
+                (Action::Fill, &["z"]),
+                (Action::Cross, &["x"]),
+                (Action::Clear, &["c"]),
+                (Action::ToggleAutoCross, &["a"]),
+                (Action::RowStart, &["0", "^"]),
+                (Action::RowEnd, &["$"]),
+                (Action::ColumnStart, &["g"]),
+                (Action::ColumnEnd, &["G"]),
+                (Action::NextUnknown, &["n"]),
+                (Action::Dot, &["."]),
+                (Action::Maybe, &["m"]),
+                (Action::GuessFill, &["Z"]),
+                (Action::GuessCross, &["X"]),
+                (Action::NextPencil, &["P"]),
+                (Action::CommitGuesses, &["C"]),
+                (Action::DiscardGuesses, &["D"]),
+                (Action::AnimateSolver, &["S"]),
+                (Action::ReplayMoves, &["R"]),
+                (Action::Faster, &["+"]),
+                (Action::Slower, &["-"]),
+            ],
+            Preset::Wasd => vec![
+                (Action::Up, &["w", "up"]),
+                (Action::Down, &["s", "down"]),
+                (Action::Left, &["a", "left"]),
+                (Action::Right, &["d", "right"]),
+                (Action::Fill, &["j"]),
+                (Action::Cross, &["k"]),
+                (Action::Clear, &["l"]),
+                (Action::ToggleAutoCross, &["t"]),
+                (Action::RowStart, &["q"]),
+                (Action::RowEnd, &["e"]),
+                (Action::ColumnStart, &["r"]),
+                (Action::ColumnEnd, &["f"]),
+                (Action::NextUnknown, &["n"]),
+                (Action::Dot, &["."]),
+                (Action::Maybe, &["m"]),
+                (Action::GuessFill, &["J"]),
+                (Action::GuessCross, &["K"]),
+                (Action::NextPencil, &["P"]),
+                (Action::CommitGuesses, &["C"]),
+                (Action::DiscardGuesses, &["V"]),
+                (Action::AnimateSolver, &["S"]),
+                (Action::ReplayMoves, &["R"]),
+                (Action::Faster, &["+"]),
+                (Action::Slower, &["-"]),
+            ],
+        }
+    }
+}
+
+/// Parse a key name into the event it produces.
+pub fn parse_key(name: &str) -> Result<Event, String> {
+    let key = match name.to_lowercase().as_str() {
+        "up" => Key::Up,
+        "down" => Key::Down,
+        "left" => Key::Left,
+        "right" => Key::Right,
+        "home" => Key::Home,
+        "end" => Key::End,
+        "pageup" => Key::PageUp,
+        "pagedown" => Key::PageDown,
+        "enter" => Key::Enter,
+        "tab" => Key::Tab,
+        "backspace" => Key::Backspace,
+        "delete" => Key::Del,
+        "insert" => Key::Ins,
+        "esc" => Key::Esc,
+        "space" => return Ok(Event::Char(' ')),
+        _ => {
+            let mut chars = name.chars();
+            return match (chars.next(), chars.next()) {
+                (Some(c), None) => Ok(Event::Char(c)),
+                _ => Err(format!("unknown key \"{}\"", name)),
+            };
+        }
+    };
+    Ok(Event::Key(key))
+}
+
+/// A key and the action it triggers.
+#[derive(Debug, Clone)]
+pub struct Binding {
+    /// The key as written in a preset or the config file.
+    pub key: String,
+    pub event: Event,
+    pub action: Action,
+}
+
+/// Which keys trigger which actions.
+#[derive(Debug, Clone)]
+pub struct KeyMap {
+    bindings: Vec<Binding>,
+}
+
+impl KeyMap {
+    pub fn from_preset(preset: Preset) -> Self {
+        let bindings = preset
+            .bindings()
+            .into_iter()
+            .flat_map(|(action, keys)| {
+                keys.iter().map(move |key| Binding {
+                    key: key.to_string(),
+                    event: parse_key(key).expect("preset keys are valid"),
+                    action,
+                })
+            })
+            .collect();
+        KeyMap { bindings }
+    }
+
+    /// Replace the keys of each action in `overrides`, keyed by action name.
+    ///
+    /// A key given here is taken away from any action the preset bound it to.
+    /// Giving the same key to two actions is an error.
+    pub fn with_overrides(
+        mut self,
+        overrides: &BTreeMap<String, Vec<String>>,
+    ) -> Result<Self, String> {
+        let mut overridden = Vec::new();
+        for (name, keys) in overrides {
+            let action: Action = name.parse()?;
+            for key in keys {
+                overridden.push(Binding {
+                    key: key.clone(),
+                    event: parse_key(key)?,
+                    action,
+                });
+            }
+            self.bindings.retain(|binding| binding.action != action);
+        }
+        for (i, binding) in overridden.iter().enumerate() {
+            if let Some(other) = overridden[..i]
+                .iter()
+                .find(|other| other.event == binding.event)
+            {
+                return Err(format!(
+                    "key \"{}\" is bound to both {} and {}",
+                    binding.key, other.action, binding.action
+                ));
+            }
+            self.bindings.retain(|preset| preset.event != binding.event);
+        }
+        self.bindings.extend(overridden);
+        Ok(self)
+    }
+
+    pub fn bindings(&self) -> &[Binding] {
+        &self.bindings
+    }
+
+    /// The first key bound to `action`, if any.
+    pub fn key_for(&self, action: Action) -> Option<&str> {
+        self.bindings
+            .iter()
+            .find(|binding| binding.action == action)
+            .map(|binding| binding.key.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action_for(map: &KeyMap, key: &str) -> Option<Action> {
+        let event = parse_key(key).unwrap();
+        map.bindings()
+            .iter()
+            .find(|binding| binding.event == event)
+            .map(|binding| binding.action)
+    }
+
+    fn overrides(entries: &[(&str, &[&str])]) -> BTreeMap<String, Vec<String>> {
+        entries
+            .iter()
+            .map(|(name, keys)| {
+                let keys = keys.iter().map(|key| key.to_string()).collect();
+                (name.to_string(), keys)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn presets_map_their_movement_keys() {
+        let cases: &[(Preset, [&str; 4])] = &[
+            (Preset::Default, ["up", "down", "left", "right"]),
+            (Preset::Vim, ["k", "j", "h", "l"]),
+            (Preset::Wasd, ["w", "s", "a", "d"]),
+        ];
+        let movement = [Action::Up, Action::Down, Action::Left, Action::Right];
+        for (preset, keys) in cases {
+            let map = KeyMap::from_preset(*preset);
+            for (key, action) in keys.iter().zip(movement.iter()) {
+                assert_eq!(action_for(&map, key), Some(*action), "{:?}", preset);
+            }
+            for (arrow, action) in ["up", "down", "left", "right"].iter().zip(movement.iter()) {
+                assert_eq!(action_for(&map, arrow), Some(*action), "{:?}", preset);
+            }
+        }
+    }
+
+    #[test]
+    fn override_takes_the_key_from_the_preset_action() {
+        let map = KeyMap::from_preset(Preset::Default)
+            .with_overrides(&overrides(&[("fill", &["x"])]))
+            .unwrap();
+        assert_eq!(action_for(&map, "x"), Some(Action::Fill));
+        assert_eq!(action_for(&map, "z"), None);
+        assert_eq!(map.key_for(Action::Cross), None);
+        let bound_to_x = map.bindings().iter().filter(|binding| binding.key == "x");
+        assert_eq!(bound_to_x.count(), 1);
+    }
+
+    #[test]
+    fn conflicting_overrides_are_an_error() {
+        let result = KeyMap::from_preset(Preset::Default)
+            .with_overrides(&overrides(&[("fill", &["q"]), ("cross", &["q"])]));
+        let error = result.unwrap_err();
+        assert!(error.contains("\"q\""), "{}", error);
+    }
+}
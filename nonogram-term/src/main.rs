@@ -1,13 +1,71 @@
-use cursive::event::{Event, Key};
+mod config;
+mod keys;
+mod playback;
+mod theme;
+
+use crate::config::Config;
+use crate::keys::{Action, KeyMap};
+use crate::playback::{Move, Playback, DELAYS};
+use crate::theme::Theme;
+use cursive::event::Event;
 use cursive::traits::*;
 use cursive::vec::Vec2;
 use cursive::view::View;
-use cursive::views::{Dialog, LinearLayout, OnEventView, Panel, ViewRef};
+use cursive::views::{Dialog, LinearLayout, OnEventView, Panel, TextView, ViewRef};
 use cursive::Cursive;
 use cursive::Printer;
-use nonogram::{Clue, LineClues, MaybeTile, Nonogram, Tile};
+use nonogram::annotation::{Annotations, Mark, Pencil};
+use nonogram::{Clue, Line, LineClues, LineState, MaybeTile, Nonogram, Tile};
+use std::env;
+use std::path::Path;
+use std::process;
+use std::rc::Rc;
+
+const USAGE: &str = "\
+Usage:
+    nonogram-term [--config FILE] [--keys PRESET] [--theme THEME]
+
+Options:
+    --config FILE  Read the config from FILE instead of
+                   $XDG_CONFIG_HOME/nonogram-term/config.toml
+    --keys PRESET  The key bindings to start from: default, vim or wasd
+    --theme THEME  How to draw the grid: unicode, ascii, blocks,
+                   high-contrast or colorblind
+    -h, --help     Print this message";
 
 pub fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut config_path = None;
+    let mut preset = None;
+    let mut theme_name = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            "--config" | "--keys" | "--theme" => {
+                let value = match args.next() {
+                    Some(value) => value.as_str(),
+                    None => usage_error(&format!("{} needs a value", arg)),
+                };
+                match arg.as_str() {
+                    "--config" => config_path = Some(Path::new(value)),
+                    "--keys" => preset = Some(value),
+                    _ => theme_name = Some(value),
+                }
+            }
+            _ => usage_error(&format!("unknown argument \"{}\"", arg)),
+        }
+    }
+    let (key_map, theme) = Config::load(config_path)
+        .and_then(|config| Ok((config.key_map(preset)?, config.theme(theme_name)?)))
+        .unwrap_or_else(|message| {
+            eprintln!("error: {}", message);
+            process::exit(1);
+        });
+
     #[rustfmt::skip]
     let row_clues = vec![
         vec![2],
@@ -25,83 +83,281 @@ pub fn main() {
 
     let mut siv = Cursive::default();
 
-    siv.add_layer(
-        OnEventView::new(
-            Dialog::new()
-                .title("Nonogram")
-                .content(
-                    LinearLayout::horizontal()
-                        .child(Panel::new(NonogramView::new(non).with_id("game"))),
-                )
-                .button("Quit game", |s| {
-                    s.quit();
-                }),
+    let key_map = Rc::new(key_map);
+    let non_view = NonogramView::new(non, theme);
+    let status = status_text(&non_view, &key_map);
+    let game = Dialog::new()
+        .title("Nonogram")
+        .content(
+            LinearLayout::vertical()
+                .child(Panel::new(non_view.with_id("game")))
+                .child(TextView::new(status).with_id("status")),
         )
-        .on_event(Event::Key(Key::Up), |s| {
-            get_nonogram_view(s).move_focus_up();
-        })
-        .on_event(Event::Key(Key::Down), |s| {
-            get_nonogram_view(s).move_focus_down();
-        })
-        .on_event(Event::Key(Key::Left), |s| {
-            get_nonogram_view(s).move_focus_left();
-        })
-        .on_event(Event::Key(Key::Right), |s| {
-            get_nonogram_view(s).move_focus_right();
-        })
-        .on_event(Event::Char('z'), |s| {
-            let mut non_view = get_nonogram_view(s);
-            non_view.toggle_filled_focused();
-            if non_view.is_correct_solution() {
-                s.add_layer(Dialog::new().title("You won!").button("Ok", |s| s.quit()));
-            }
-        })
-        .on_event(Event::Char('x'), |s| {
-            get_nonogram_view(s).toggle_not_filled_focused();
-        })
-        .on_event(Event::Char('c'), |s| {
-            get_nonogram_view(s).clear_focused();
-        }),
-    );
+        .button("Quit game", |s| {
+            s.quit();
+        });
+    let game = key_map
+        .bindings()
+        .iter()
+        .fold(OnEventView::new(game), |view, binding| {
+            let action = binding.action;
+            let key_map = Rc::clone(&key_map);
+            view.on_event(binding.event.clone(), move |s| perform(s, action, &key_map))
+        });
+    siv.add_layer(game);
+
+    // Animations advance on refreshes, which happen this many times a second
+    siv.set_fps(30);
+    siv.add_global_callback(Event::Refresh, move |s| {
+        let mut non_view = get_nonogram_view(s);
+        if non_view.tick() {
+            drop(non_view);
+            update_status(s, &key_map);
+        }
+    });
 
     siv.run();
 }
 
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}\n\n{}", message, USAGE);
+    process::exit(2);
+}
+
+fn perform(s: &mut Cursive, action: Action, key_map: &Rc<KeyMap>) {
+    let mut non_view = get_nonogram_view(s);
+    let is_playback_action = matches!(
+        action,
+        Action::AnimateSolver | Action::ReplayMoves | Action::Faster | Action::Slower
+    );
+    // Any other key goes back to the puzzle rather than changing it unseen,
+    // and once the animation is over there is nothing left to speed up
+    if non_view.is_playing() && (!is_playback_action || non_view.is_playback_finished()) {
+        non_view.stop_playback();
+        drop(non_view);
+        update_status(s, key_map);
+        return;
+    }
+    match action {
+        Action::Up => non_view.move_focus_up(),
+        Action::Down => non_view.move_focus_down(),
+        Action::Left => non_view.move_focus_left(),
+        Action::Right => non_view.move_focus_right(),
+        Action::RowStart => non_view.move_focus_row_start(),
+        Action::RowEnd => non_view.move_focus_row_end(),
+        Action::ColumnStart => non_view.move_focus_column_start(),
+        Action::ColumnEnd => non_view.move_focus_column_end(),
+        Action::NextUnknown => non_view.move_focus_next_unknown(),
+        Action::Fill => non_view.toggle_filled_focused(),
+        Action::Cross => non_view.toggle_not_filled_focused(),
+        Action::Clear => non_view.clear_focused(),
+        Action::ToggleAutoCross => non_view.toggle_auto_cross(),
+        Action::Dot => non_view.toggle_mark_focused(Mark::Dot),
+        Action::Maybe => non_view.toggle_mark_focused(Mark::Maybe),
+        Action::GuessFill => non_view.toggle_guess_focused(Tile::Filled),
+        Action::GuessCross => non_view.toggle_guess_focused(Tile::NotFilled),
+        Action::NextPencil => non_view.next_pencil(),
+        Action::CommitGuesses => non_view.commit_guesses(),
+        Action::DiscardGuesses => non_view.discard_guesses(),
+        Action::AnimateSolver => non_view.animate_solver(),
+        Action::ReplayMoves => non_view.replay_moves(),
+        Action::Faster => non_view.faster(),
+        Action::Slower => non_view.slower(),
+    }
+    let can_win = action == Action::Fill || action == Action::CommitGuesses;
+    let has_won = can_win && non_view.is_correct_solution();
+    drop(non_view);
+    update_status(s, key_map);
+    if has_won {
+        let key_map = Rc::clone(key_map);
+        s.add_layer(
+            Dialog::new()
+                .title("You won!")
+                .button("Replay", move |s| {
+                    s.pop_layer();
+                    get_nonogram_view(s).replay_moves();
+                    update_status(s, &key_map);
+                })
+                .button("Ok", |s| s.quit()),
+        );
+    }
+}
+
+fn update_status(s: &mut Cursive, key_map: &KeyMap) {
+    let status = status_text(&get_nonogram_view(s), key_map);
+    s.find_id::<TextView>("status").unwrap().set_content(status);
+}
+
 fn get_nonogram_view(s: &mut Cursive) -> ViewRef<NonogramView> {
     s.find_id::<NonogramView>("game").unwrap()
 }
 
+fn status_text(non_view: &NonogramView, key_map: &KeyMap) -> String {
+    if let Some(description) = non_view.playback_description() {
+        if non_view.is_playback_finished() {
+            return format!("{}\nPress any key to return to the puzzle", description);
+        }
+        let speed = format!("Speed: {} of {}", non_view.speed() + 1, DELAYS.len());
+        let speed = match (
+            key_map.key_for(Action::Faster),
+            key_map.key_for(Action::Slower),
+        ) {
+            (Some(faster), Some(slower)) => {
+                format!("{} ({} faster, {} slower)", speed, faster, slower)
+            }
+            _ => speed,
+        };
+        return format!(
+            "{}\n{}\nAny other key returns to the puzzle",
+            description, speed
+        );
+    }
+    let state = if non_view.is_auto_cross() {
+        "on"
+    } else {
+        "off"
+    };
+    let auto_cross = match key_map.key_for(Action::ToggleAutoCross) {
+        Some(key) => format!("Auto-cross completed lines: {} ({} to toggle)", state, key),
+        None => format!("Auto-cross completed lines: {}", state),
+    };
+    let run = match non_view.focused_run_lengths() {
+        (0, 0) => "Filled run: none".to_string(),
+        (across, down) => format!("Filled run: {} across, {} down", across, down),
+    };
+    let pencil = format!(
+        "pencil {} of {}",
+        non_view.pencil() + 1,
+        non_view.num_pencils()
+    );
+    let pencil = match key_map.key_for(Action::NextPencil) {
+        Some(key) => format!("{} ({} to switch)", pencil, key),
+        None => pencil,
+    };
+    let guesses = match non_view.num_guesses() {
+        0 => "Guesses: none".to_string(),
+        num_guesses => match (
+            key_map.key_for(Action::CommitGuesses),
+            key_map.key_for(Action::DiscardGuesses),
+        ) {
+            (Some(commit), Some(discard)) => format!(
+                "Guesses: {} ({} to commit, {} to discard)",
+                num_guesses, commit, discard
+            ),
+            _ => format!("Guesses: {}", num_guesses),
+        },
+    };
+    format!("{}\n{}\n{}, {}", auto_cross, run, guesses, pencil)
+}
+
 pub struct NonogramView {
     nonogram: Nonogram,
     focus: (usize, usize),
+    theme: Theme,
+    /// The player's marks, which never change the answer.
+    annotations: Annotations,
+    /// The pencil new guesses are made with, an index into the theme's
+    /// pencil styles.
+    pencil: Pencil,
     max_num_row_clues: usize,
     max_row_clue_width: usize,
     max_num_column_clues: usize,
     max_column_clue_width: usize,
+    /// Whether to cross out the remaining unknown tiles of a line as soon as
+    /// its filled tiles satisfy its clues.
+    auto_cross: bool,
+    /// Every change the player made to a tile, oldest first.
+    moves: Vec<Move>,
+    /// The animation shown in place of the player's grid, if any.
+    playback: Option<Playback>,
+    /// An index into [DELAYS].
+    speed: usize,
 }
 
 impl NonogramView {
-    const FILLED_STRING: &'static str = "▣";
-    const NOT_FILLED_STRING: &'static str = "☒";
-    const UNKNOWN_STRING: &'static str = "☐";
-    // const FILLED_STRING: &'static str = "#";
-    // const NOT_FILLED_STRING: &'static str = "X";
-    // const UNKNOWN_STRING: &'static str = "_";
-    const TOP_DIVIDER: char = '─';
-    const SIDE_DIVIDER: char = '│';
-    const CORNER_DIVIDER: char = '┌';
-
-    pub fn new(nonogram: Nonogram) -> Self {
+    pub fn new(nonogram: Nonogram, theme: Theme) -> Self {
         NonogramView {
             max_num_row_clues: get_max_num_row_clues(&nonogram),
             max_row_clue_width: get_max_row_clue_width(&nonogram),
             max_num_column_clues: get_max_num_column_clues(&nonogram),
             max_column_clue_width: get_max_column_clue_width(&nonogram),
+            annotations: Annotations::for_nonogram(&nonogram),
+            pencil: 0,
             nonogram,
             focus: (0, 0),
+            theme,
+            auto_cross: false,
+            moves: Vec::new(),
+            playback: None,
+            speed: 2,
         }
     }
 
+    pub fn is_auto_cross(&self) -> bool {
+        self.auto_cross
+    }
+
+    /// Turn auto-crossing on or off. Turning it on immediately crosses out
+    /// every line that is already satisfied.
+    pub fn toggle_auto_cross(&mut self) {
+        self.auto_cross = !self.auto_cross;
+        if self.auto_cross {
+            self.cross_all_satisfied();
+        }
+    }
+
+    /// Add `mark` to the focused tile if it's unknown, or remove it if it's
+    /// already there.
+    pub fn toggle_mark_focused(&mut self, mark: Mark) {
+        let (row, column) = self.focus;
+        if self.get_focused().is_none() {
+            self.annotations.toggle(row, column, mark);
+        }
+    }
+
+    /// Guess `tile` in the current pencil on the focused tile if it's
+    /// unknown, or remove the guess if it's already there.
+    pub fn toggle_guess_focused(&mut self, tile: Tile) {
+        self.toggle_mark_focused(Mark::Guess(tile, self.pencil));
+    }
+
+    pub fn pencil(&self) -> Pencil {
+        self.pencil
+    }
+
+    pub fn num_pencils(&self) -> usize {
+        self.theme.pencil_styles.len()
+    }
+
+    /// Make new guesses with the next pencil, so they can be told apart from
+    /// the ones made so far.
+    pub fn next_pencil(&mut self) {
+        self.pencil = (self.pencil + 1) % self.num_pencils();
+    }
+
+    pub fn num_guesses(&self) -> usize {
+        self.annotations.guesses().len()
+    }
+
+    /// Turn every guess into a tile.
+    pub fn commit_guesses(&mut self) {
+        // Only unknown tiles can be guessed, so every guess changes a tile
+        let guesses = self.annotations.commit_guesses(&mut self.nonogram);
+        self.moves.extend(
+            guesses
+                .into_iter()
+                .map(|(location, tile)| (location, Some(tile))),
+        );
+        if self.auto_cross {
+            self.cross_all_satisfied();
+        }
+    }
+
+    /// Remove every guess, leaving the tiles as they were before guessing.
+    pub fn discard_guesses(&mut self) {
+        self.annotations.discard_guesses();
+    }
+
     pub fn toggle_filled_focused(&mut self) {
         if let Some(Tile::Filled) = self.get_focused() {
             self.clear_focused();
@@ -120,45 +376,161 @@ impl NonogramView {
 
     pub fn clear_focused(&mut self) {
         let (row, column) = self.focus;
-        self.nonogram.unset_tile(row, column);
+        self.change_tile(row, column, None);
     }
 
-    pub fn move_focus_up(&mut self) {
-        let (mut row, column) = self.focus;
-        if row > 0 {
-            row -= 1;
+    /// Start animating the solver on an empty grid, or stop if it's already
+    /// running.
+    pub fn animate_solver(&mut self) {
+        match &self.playback {
+            Some(playback) if !playback.is_replay() => self.stop_playback(),
+            _ => self.playback = Some(Playback::solver(&self.nonogram)),
         }
-        self.focus = (row, column);
+    }
+
+    /// Start replaying the player's moves on an empty grid, or stop if a
+    /// replay is already running.
+    pub fn replay_moves(&mut self) {
+        match &self.playback {
+            Some(playback) if playback.is_replay() => self.stop_playback(),
+            _ => self.playback = Some(Playback::replay(&self.nonogram, self.moves.clone())),
+        }
+    }
+
+    /// Go back to showing the player's grid.
+    pub fn stop_playback(&mut self) {
+        self.playback = None;
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playback.is_some()
+    }
+
+    pub fn is_playback_finished(&self) -> bool {
+        self.playback.as_ref().is_some_and(Playback::is_finished)
+    }
+
+    pub fn playback_description(&self) -> Option<&str> {
+        self.playback.as_ref().map(Playback::description)
+    }
+
+    pub fn speed(&self) -> usize {
+        self.speed
+    }
+
+    pub fn faster(&mut self) {
+        if self.speed < DELAYS.len() - 1 {
+            self.speed += 1;
+        }
+    }
+
+    pub fn slower(&mut self) {
+        if self.speed > 0 {
+            self.speed -= 1;
+        }
+    }
+
+    /// Advance the animation if it's time for its next frame. Returns whether
+    /// it changed.
+    pub fn tick(&mut self) -> bool {
+        let delay = DELAYS[self.speed];
+        match &mut self.playback {
+            Some(playback) => playback.tick(delay),
+            None => false,
+        }
+    }
+
+    pub fn move_focus_up(&mut self) {
+        let (row, column) = self.focus;
+        self.focus = (row.saturating_sub(1), column);
     }
 
     pub fn move_focus_down(&mut self) {
         let (mut row, column) = self.focus;
-        if row < self.nonogram.num_rows() - 1 {
+        if row + 1 < self.nonogram.num_rows() {
             row += 1;
         }
         self.focus = (row, column);
     }
 
     pub fn move_focus_left(&mut self) {
-        let (row, mut column) = self.focus;
-        if column > 0 {
-            column -= 1;
-        }
-        self.focus = (row, column);
+        let (row, column) = self.focus;
+        self.focus = (row, column.saturating_sub(1));
     }
 
     pub fn move_focus_right(&mut self) {
         let (row, mut column) = self.focus;
-        if column < self.nonogram.num_cols() - 1 {
+        if column + 1 < self.nonogram.num_cols() {
             column += 1;
         }
         self.focus = (row, column);
     }
 
+    pub fn move_focus_row_start(&mut self) {
+        self.focus.1 = 0;
+    }
+
+    pub fn move_focus_row_end(&mut self) {
+        self.focus.1 = self.nonogram.num_cols().saturating_sub(1);
+    }
+
+    pub fn move_focus_column_start(&mut self) {
+        self.focus.0 = 0;
+    }
+
+    pub fn move_focus_column_end(&mut self) {
+        self.focus.0 = self.nonogram.num_rows().saturating_sub(1);
+    }
+
+    /// Move the focus to the next unknown tile, reading left to right and top
+    /// to bottom and wrapping around to the first row. The focus stays put if
+    /// every other tile is known.
+    pub fn move_focus_next_unknown(&mut self) {
+        let num_cols = self.nonogram.num_cols();
+        let num_tiles = self.nonogram.num_rows() * num_cols;
+        let (row, column) = self.focus;
+        let start = row * num_cols + column;
+        let next = (1..num_tiles)
+            .map(|offset| (start + offset) % num_tiles)
+            .map(|index| (index / num_cols, index % num_cols))
+            .find(|&(row, column)| self.nonogram.get_tile(row, column).is_none());
+        if let Some(focus) = next {
+            self.focus = focus;
+        }
+    }
+
     pub fn is_correct_solution(&self) -> bool {
         self.nonogram.is_correct_solution()
     }
 
+    /// The number of filled tiles in an unbroken run through the focused
+    /// tile, across its row and down its column.
+    pub fn focused_run_lengths(&self) -> (usize, usize) {
+        let (row, column) = self.focus;
+        (
+            self.run_length_through(Line::Row(row), column),
+            self.run_length_through(Line::Column(column), row),
+        )
+    }
+
+    fn run_length_through(&self, line: Line, index: usize) -> usize {
+        let is_filled = |index: usize| {
+            let (row, column) = line.position(index);
+            match self.nonogram.get_tile(row, column) {
+                Some(Tile::NotFilled) | None => false,
+                Some(_) => true,
+            }
+        };
+        if !is_filled(index) {
+            return 0;
+        }
+        let start = (0..index).rev().take_while(|&i| is_filled(i)).count();
+        let end = (index + 1..self.nonogram.line_len(line))
+            .take_while(|&i| is_filled(i))
+            .count();
+        start + 1 + end
+    }
+
     fn get_focused(&self) -> MaybeTile {
         let (row, column) = self.focus;
         self.nonogram.get_tile(row, column)
@@ -166,7 +538,46 @@ impl NonogramView {
 
     fn set_focused(&mut self, tile: Tile) {
         let (row, column) = self.focus;
-        self.nonogram.set_tile(row, column, tile);
+        self.change_tile(row, column, Some(tile));
+        if self.auto_cross {
+            self.cross_if_satisfied(Line::Row(row));
+            self.cross_if_satisfied(Line::Column(column));
+        }
+    }
+
+    /// Set or unset a tile, removing its mark and recording the move for
+    /// replays.
+    fn change_tile(&mut self, row: usize, column: usize, tile: MaybeTile) {
+        self.annotations.unset(row, column);
+        if self.nonogram.get_tile(row, column) == tile {
+            return;
+        }
+        match tile {
+            Some(tile) => self.nonogram.set_tile(row, column, tile),
+            None => self.nonogram.unset_tile(row, column),
+        }
+        self.moves.push(((row, column), tile));
+    }
+
+    fn cross_all_satisfied(&mut self) {
+        let lines: Vec<Line> = self.nonogram.lines().collect();
+        for line in lines {
+            self.cross_if_satisfied(line);
+        }
+    }
+
+    /// Cross out every unknown tile in `line` if its filled tiles already
+    /// satisfy its clues.
+    fn cross_if_satisfied(&mut self, line: Line) {
+        if self.nonogram.line_state(line) != LineState::Satisfied {
+            return;
+        }
+        for index in 0..self.nonogram.line_len(line) {
+            let (row, column) = line.position(index);
+            if self.nonogram.get_tile(row, column).is_none() {
+                self.change_tile(row, column, Some(Tile::NotFilled));
+            }
+        }
     }
 
     fn draw_all_row_clues(&self, printer: &Printer) {
@@ -185,25 +596,47 @@ impl NonogramView {
         let row = self.nonogram.row_clues_at(row_index);
         let num_blank_spaces = self.max_num_row_clues - row.len();
         let x_offset = num_blank_spaces * self.row_clue_space_width();
-        let y_offset = self.max_num_column_clues + 1;
         for (j, clue) in row.iter().enumerate() {
             let x = x_offset + self.row_clue_space_width() * j;
-            let y = y_offset + row_index;
+            let y = self.grid_y(row_index);
             let position = (x, y);
-            NonogramView::draw_clue(*clue, position, self.row_clue_space_width(), printer);
+            let width = self.row_clue_space_width();
+            if self.is_highlighted(Line::Row(row_index)) {
+                printer.with_color(self.theme.clue_focus_style, |p| {
+                    NonogramView::draw_clue(*clue, position, width, p)
+                });
+            } else {
+                NonogramView::draw_clue(*clue, position, width, printer);
+            }
         }
     }
 
     fn draw_column_clues(&self, column_index: usize, printer: &Printer) {
         let column = self.nonogram.column_clues_at(column_index);
         let num_blank_spaces = self.max_num_column_clues - column.len();
-        let x_offset = self.max_num_row_clues * self.row_clue_space_width();
         let y_offset = num_blank_spaces;
         for (j, clue) in column.iter().enumerate() {
-            let x = x_offset + column_index * self.column_clue_space_width();
+            // Right aligned so the last digit is above the tile
+            let x = self.grid_x(column_index) - 1;
             let y = y_offset + j;
             let position = (x, y);
-            NonogramView::draw_clue(*clue, position, self.column_clue_space_width(), printer);
+            let width = self.column_clue_space_width();
+            if self.is_highlighted(Line::Column(column_index)) {
+                printer.with_color(self.theme.clue_focus_style, |p| {
+                    NonogramView::draw_clue(*clue, position, width, p)
+                });
+            } else {
+                NonogramView::draw_clue(*clue, position, width, printer);
+            }
+        }
+    }
+
+    /// Whether to highlight the clues of `line`: the focused row and column,
+    /// or the line the solver examined during an animation.
+    fn is_highlighted(&self, line: Line) -> bool {
+        match &self.playback {
+            Some(playback) => playback.line() == Some(line),
+            None => line == Line::Row(self.focus.0) || line == Line::Column(self.focus.1),
         }
     }
 
@@ -213,37 +646,119 @@ impl NonogramView {
     }
 
     fn draw_grid(&self, printer: &Printer) {
+        self.draw_guides(printer);
         for i in 0..self.nonogram.num_rows() {
             self.draw_grid_row(i, printer);
         }
     }
 
     fn draw_grid_row(&self, index: usize, printer: &Printer) {
-        for (j, maybe_tile) in self.nonogram.get_row(index).enumerate() {
+        for (j, maybe_tile) in self.shown_nonogram().get_row(index).enumerate() {
             let location = (index, j);
             self.draw_tile(*maybe_tile, location, printer);
         }
     }
 
+    /// The animation's grid while there is one, or else the player's.
+    fn shown_nonogram(&self) -> &Nonogram {
+        match &self.playback {
+            Some(playback) => playback.nonogram(),
+            None => &self.nonogram,
+        }
+    }
+
     fn draw_tile(&self, tile: MaybeTile, location: (usize, usize), printer: &Printer) {
         let (row, column) = location;
-        // all row/column clues + 1 for divider
-        let x_offset = self.max_num_row_clues * self.row_clue_space_width() + 1;
-        let y_offset = self.max_num_column_clues + 1;
-        let x = x_offset + NonogramView::cell_width() * column;
-        let y = y_offset + row;
-        let position = (x, y);
-        let s = format!(
-            "{:<width$}",
-            NonogramView::maybe_tile_to_string(tile),
-            width = NonogramView::get_max_cell_width()
-        );
-        if location == self.focus {
-            printer.with_color(cursive::theme::ColorStyle::highlight(), |p| {
-                p.print(position, &s)
-            });
+        let position = (self.grid_x(column), self.grid_y(row));
+        // Marks are the player's, so they aren't part of an animation
+        let mark = match self.playback {
+            Some(_) => None,
+            None => self.annotations.get(row, column),
+        };
+        let (glyph, tile_style) = match (tile, mark) {
+            (Some(Tile::NotFilled), _) => (&self.theme.not_filled, self.theme.not_filled_style),
+            (Some(_), _) => (&self.theme.filled, self.theme.filled_style),
+            (None, Some(Mark::Dot)) => (&self.theme.dot, None),
+            (None, Some(Mark::Maybe)) => (&self.theme.maybe, None),
+            (None, Some(Mark::Guess(tile, pencil))) => {
+                let glyph = match tile {
+                    Tile::NotFilled => &self.theme.not_filled,
+                    _ => &self.theme.filled,
+                };
+                let style = self.theme.pencil_styles.get(pencil).copied();
+                (glyph, style)
+            }
+            (None, None) => (&self.theme.unknown, None),
+        };
+        let s = format!("{:<width$}", glyph, width = self.theme.cell_width);
+        let is_crosshair = row == self.focus.0 || column == self.focus.1;
+        let style = if let Some(playback) = &self.playback {
+            if playback.is_changed(row, column) {
+                Some(self.theme.changed_style)
+            } else if self.is_highlighted(Line::Row(row))
+                || self.is_highlighted(Line::Column(column))
+            {
+                Some(self.theme.examined_style)
+            } else {
+                tile_style
+            }
+        } else if location == self.focus {
+            Some(self.theme.focus_style)
+        } else if is_crosshair && self.theme.crosshair {
+            Some(self.theme.crosshair_style)
         } else {
-            printer.print(position, &s);
+            tile_style
+        };
+        match style {
+            Some(style) => printer.with_color(style, |p| p.print(position, &s)),
+            None => printer.print(position, &s),
+        }
+    }
+
+    /// Draw a line before each row and column that starts a new group of
+    /// guides.
+    fn draw_guides(&self, printer: &Printer) {
+        let guide_columns: Vec<usize> = (1..self.nonogram.num_cols())
+            .filter(|&column| self.is_guide_at(column))
+            .collect();
+        let guide_rows: Vec<usize> = (1..self.nonogram.num_rows())
+            .filter(|&row| self.is_guide_at(row))
+            .collect();
+        let side = self.theme.side_divider.to_string();
+        for &column in &guide_columns {
+            let x = self.grid_x(column) - 1;
+            for y in self.grid_y(0)..self.grid_y(0) + self.grid_height() {
+                printer.print((x, y), &side);
+            }
+        }
+        let line = self.theme.top_divider.to_string().repeat(self.grid_width());
+        let cross = self.theme.cross_divider.to_string();
+        for &row in &guide_rows {
+            let y = self.grid_y(row) - 1;
+            printer.print((self.grid_x(0), y), &line);
+            for &column in &guide_columns {
+                printer.print((self.grid_x(column) - 1, y), &cross);
+            }
+        }
+    }
+
+    /// Number the first row and column and every guide, or every fifth one
+    /// without guides, to the right of and below the grid.
+    fn draw_rulers(&self, printer: &Printer) {
+        let x = self.grid_x(0) + self.grid_width() + 1;
+        for row in (0..self.nonogram.num_rows()).filter(|&row| self.is_ruler_at(row)) {
+            printer.print((x, self.grid_y(row)), &(row + 1).to_string());
+        }
+        let y = self.grid_y(0) + self.grid_height();
+        let mut end = 0;
+        for column in (0..self.nonogram.num_cols()).filter(|&column| self.is_ruler_at(column)) {
+            let x = self.grid_x(column);
+            let label = (column + 1).to_string();
+            // Labels need a space between them
+            if column == 0 || x > end {
+                printer.print((x, y), &label);
+                end = x + label.len();
+            }
         }
     }
 
@@ -254,106 +769,99 @@ impl NonogramView {
     }
 
     fn draw_top_border(&self, printer: &Printer) {
-        let x = self.max_num_row_clues * self.row_clue_space_width() + 1;
-        let y = self.max_num_column_clues;
-        let position = (x, y);
-        let width = self.nonogram.num_cols() * NonogramView::cell_width();
-        let s = NonogramView::TOP_DIVIDER.to_string().repeat(width);
+        let position = (self.grid_x(0), self.grid_y(0) - 1);
+        let s = self.theme.top_divider.to_string().repeat(self.grid_width());
         printer.print(position, &s);
     }
 
     fn draw_side_border(&self, printer: &Printer) {
-        let x = self.max_num_row_clues * self.row_clue_space_width();
-        let y_offset = self.max_num_column_clues + 1;
-        let s = NonogramView::SIDE_DIVIDER.to_string();
-        for j in 0..self.nonogram.num_rows() {
-            let y = y_offset + j;
-            let position = (x, y);
-            printer.print(position, &s);
+        let x = self.grid_x(0) - 1;
+        let s = self.theme.side_divider.to_string();
+        for y in self.grid_y(0)..self.grid_y(0) + self.grid_height() {
+            printer.print((x, y), &s);
         }
     }
 
     fn draw_corner_border(&self, printer: &Printer) {
-        let x = self.max_num_row_clues * self.row_clue_space_width();
-        let y = self.max_num_column_clues;
-        let position = (x, y);
-        let s = NonogramView::CORNER_DIVIDER.to_string();
+        let position = (self.grid_x(0) - 1, self.grid_y(0) - 1);
+        let s = self.theme.corner_divider.to_string();
         printer.print(position, &s);
     }
 
-    fn row_clue_space_width(&self) -> usize {
-        self.max_row_clue_width + 1
+    /// The x position of the tiles of `column`, after all row clues, the
+    /// divider and any guides before it.
+    fn grid_x(&self, column: usize) -> usize {
+        let x_offset = self.max_num_row_clues * self.row_clue_space_width() + 1;
+        x_offset + self.cell_width() * column + self.num_guides_before(column)
     }
 
-    fn column_clue_space_width(&self) -> usize {
-        self.max_column_clue_width + 1
+    /// The y position of the tiles of `row`, after all column clues, the
+    /// divider and any guides before it.
+    fn grid_y(&self, row: usize) -> usize {
+        let y_offset = self.max_num_column_clues + 1;
+        y_offset + row + self.num_guides_before(row)
     }
 
-    fn maybe_tile_to_string(maybe_tile: MaybeTile) -> &'static str {
-        match maybe_tile {
-            Some(Tile::Filled) => NonogramView::FILLED_STRING,
-            Some(Tile::FilledWithColor(_)) => NonogramView::FILLED_STRING,
-            Some(Tile::NotFilled) => NonogramView::NOT_FILLED_STRING,
-            None => NonogramView::UNKNOWN_STRING,
+    fn grid_width(&self) -> usize {
+        let num_cols = self.nonogram.num_cols();
+        num_cols * self.cell_width() + self.num_guides_before(num_cols.saturating_sub(1))
+    }
+
+    fn grid_height(&self) -> usize {
+        let num_rows = self.nonogram.num_rows();
+        num_rows + self.num_guides_before(num_rows.saturating_sub(1))
+    }
+
+    fn is_guide_at(&self, index: usize) -> bool {
+        match self.theme.guide_every {
+            Some(every) => index > 0 && index % every == 0,
+            None => false,
         }
     }
 
-    #[allow(dead_code)]
-    fn get_max_string_width(&self, nonogram: &Nonogram) -> usize {
-        *[
-            get_max_row_clue_width(nonogram),
-            get_max_column_clue_width(nonogram),
-            NonogramView::get_max_cell_width(),
-        ]
-        .into_iter()
-        .max()
-        .unwrap()
+    fn num_guides_before(&self, index: usize) -> usize {
+        self.theme.guide_every.map_or(0, |every| index / every)
     }
 
-    fn cell_width() -> usize {
-        NonogramView::get_max_cell_width() + 1
+    fn is_ruler_at(&self, index: usize) -> bool {
+        let step = self.theme.guide_every.unwrap_or(5);
+        index == 0 || (index + 1) % step == 0
     }
 
-    fn get_max_cell_width() -> usize {
-        1
-        // 2
-        // [
-        //     NonogramView::FILLED_STRING,
-        //     NonogramView::NOT_FILLED_STRING,
-        //     NonogramView::UNKNOWN_STRING,
-        // ]
-        // .into_iter()
-        // .map(|s| s.len())
-        // .max()
-        // .unwrap()
+    fn row_clue_space_width(&self) -> usize {
+        self.max_row_clue_width + 1
+    }
+
+    fn column_clue_space_width(&self) -> usize {
+        self.max_column_clue_width + 1
+    }
+
+    /// The width of each column of the grid: the tile and the gap after it,
+    /// widened to fit the column clues if they need more.
+    fn cell_width(&self) -> usize {
+        self.theme.cell_stride().max(self.column_clue_space_width())
     }
 }
 
 impl View for NonogramView {
     fn draw(&self, printer: &Printer) {
-        // for i in 0..self.nonogram.num_rows() {
-        //     let row = self.nonogram.get_row(i);
-        //     printer.print((0, i), " ");
-        //     for (j, maybe_tile) in row.iter().enumerate() {
-        //         let text = NonogramView::maybe_tile_to_string(*maybe_tile);
-        //         printer.print((j * 2 + 1, i), text);
-        //         printer.print((j * 2 + 2, i), " ");
-        //     }
-        // }
         self.draw_all_row_clues(printer);
         self.draw_all_column_clues(printer);
         self.draw_borders(printer);
         self.draw_grid(printer);
+        if self.theme.rulers {
+            self.draw_rulers(printer);
+        }
     }
 
     fn required_size(&mut self, _constraint: Vec2) -> Vec2 {
-        let row_clues_width = self.max_num_row_clues * self.row_clue_space_width();
-        let column_clues_height = self.max_num_column_clues;
-        let grid_width = self.nonogram.num_cols() * NonogramView::cell_width();
-        let grid_height = self.nonogram.num_rows();
         // Clues + divider + grid
-        let width = row_clues_width + 1 + grid_width;
-        let height = column_clues_height + 1 + grid_height;
+        let mut width = self.grid_x(0) + self.grid_width();
+        let mut height = self.grid_y(0) + self.grid_height();
+        if self.theme.rulers {
+            width += 1 + self.nonogram.num_rows().to_string().len();
+            height += 1;
+        }
         (width, height).into()
     }
 }
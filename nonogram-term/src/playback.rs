@@ -0,0 +1,227 @@
+//! Animations drawn in place of the player's grid
+//!
+//! A [Playback] either shows the solver working through the puzzle one
+//! [Step](nonogram::steps::Step) at a time, or replays the moves the player
+//! made. Both start from an empty grid and leave the player's grid alone.
+
+use nonogram::solver::Status;
+use nonogram::steps::{self, Steps};
+use nonogram::{Line, MaybeTile, Nonogram};
+use std::time::{Duration, Instant};
+
+/// A tile the player changed, and what it was changed to.
+pub type Move = ((usize, usize), MaybeTile);
+
+/// The time between frames at each speed, slowest first.
+pub const DELAYS: [Duration; 5] = [
+    Duration::from_millis(1000),
+    Duration::from_millis(500),
+    Duration::from_millis(250),
+    Duration::from_millis(100),
+    Duration::from_millis(25),
+];
+
+enum Source {
+    // Boxed, as steps are much larger than a replay
+    Solver { steps: Box<Steps>, num_steps: usize },
+    Replay { moves: Vec<Move>, next: usize },
+}
+
+pub struct Playback {
+    source: Source,
+    nonogram: Nonogram,
+    /// The line examined to make the last change, if any.
+    line: Option<Line>,
+    /// The tiles changed in the last frame.
+    changes: Vec<(usize, usize)>,
+    description: String,
+    is_finished: bool,
+    last_frame: Instant,
+}
+
+impl Playback {
+    /// Animate the solver on `non`'s clues.
+    pub fn solver(non: &Nonogram) -> Self {
+        let empty = empty_copy(non);
+        let steps = steps::steps(&empty).with_probing();
+        Playback::new(
+            Source::Solver {
+                steps: Box::new(steps),
+                num_steps: 0,
+            },
+            empty,
+        )
+    }
+
+    /// Replay `moves` on `non`'s clues.
+    pub fn replay(non: &Nonogram, moves: Vec<Move>) -> Self {
+        Playback::new(Source::Replay { moves, next: 0 }, empty_copy(non))
+    }
+
+    fn new(source: Source, nonogram: Nonogram) -> Self {
+        let description = match source {
+            Source::Solver { .. } => "Solving",
+            Source::Replay { .. } => "Replaying",
+        };
+        Playback {
+            source,
+            nonogram,
+            line: None,
+            changes: Vec::new(),
+            description: description.to_string(),
+            is_finished: false,
+            last_frame: Instant::now(),
+        }
+    }
+
+    pub fn nonogram(&self) -> &Nonogram {
+        &self.nonogram
+    }
+
+    pub fn line(&self) -> Option<Line> {
+        self.line
+    }
+
+    pub fn is_changed(&self, row: usize, column: usize) -> bool {
+        self.changes.contains(&(row, column))
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.is_finished
+    }
+
+    pub fn is_replay(&self) -> bool {
+        match self.source {
+            Source::Solver { .. } => false,
+            Source::Replay { .. } => true,
+        }
+    }
+
+    /// Show the next frame if `delay` has passed since the last one. Returns
+    /// whether there was a new frame.
+    pub fn tick(&mut self, delay: Duration) -> bool {
+        if self.is_finished || self.last_frame.elapsed() < delay {
+            return false;
+        }
+        self.last_frame = Instant::now();
+        self.advance();
+        true
+    }
+
+    fn advance(&mut self) {
+        self.changes.clear();
+        self.line = None;
+        match &mut self.source {
+            Source::Solver { steps, num_steps } => match steps.next() {
+                Some(step) => {
+                    *num_steps += 1;
+                    for &((row, column), tile) in &step.changes {
+                        self.nonogram.set_tile(row, column, tile);
+                        self.changes.push((row, column));
+                    }
+                    self.line = Some(step.line);
+                    self.description =
+                        format!("Step {}: {} ({})", num_steps, step.line, step.technique);
+                }
+                None => {
+                    self.is_finished = true;
+                    self.description = match steps.result() {
+                        Some(Ok(Status::Solved)) => "Solved".to_string(),
+                        Some(Ok(Status::Stalled)) => {
+                            "Stalled: logic alone can't go further".to_string()
+                        }
+                        Some(Err(contradiction)) => format!("Stopped: {}", contradiction),
+                        None => unreachable!("steps are only finished once there is a result"),
+                    };
+                }
+            },
+            Source::Replay { moves, next } => match moves.get(*next) {
+                Some(&((row, column), tile)) => {
+                    *next += 1;
+                    match tile {
+                        Some(tile) => self.nonogram.set_tile(row, column, tile),
+                        None => self.nonogram.unset_tile(row, column),
+                    }
+                    self.changes.push((row, column));
+                    self.description = format!("Move {} of {}", next, moves.len());
+                }
+                None => {
+                    self.is_finished = true;
+                    self.description = format!("Replayed all {} moves", moves.len());
+                }
+            },
+        }
+    }
+}
+
+/// A copy of `non`, clue colors included, with every tile unknown.
+fn empty_copy(non: &Nonogram) -> Nonogram {
+    let row_clues = (0..non.num_rows())
+        .map(|index| non.colored_clues(Line::Row(index)))
+        .collect();
+    let column_clues = (0..non.num_cols())
+        .map(|index| non.colored_clues(Line::Column(index)))
+        .collect();
+    Nonogram::colored(row_clues, column_clues)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nonogram::{ColoredClue, Tile};
+
+    fn colored_nonogram() -> Nonogram {
+        let clue = |length, color| ColoredClue {
+            length,
+            color: Some(color),
+        };
+        Nonogram::colored(
+            vec![vec![clue(1, 'a'), clue(1, 'b')]],
+            vec![vec![clue(1, 'a')], vec![clue(1, 'b')]],
+        )
+    }
+
+    fn play_to_end(playback: &mut Playback) {
+        while !playback.is_finished() {
+            playback.tick(Duration::from_millis(0));
+        }
+    }
+
+    #[test]
+    fn solver_plays_back_a_colored_puzzle() {
+        let non = colored_nonogram();
+        let mut playback = Playback::solver(&non);
+        assert!(playback.nonogram().is_colored());
+        play_to_end(&mut playback);
+        assert_eq!(playback.description(), "Solved");
+        assert_eq!(
+            playback.nonogram().get_tile(0, 0),
+            Some(Tile::FilledWithColor('a'))
+        );
+        assert_eq!(
+            playback.nonogram().get_tile(0, 1),
+            Some(Tile::FilledWithColor('b'))
+        );
+        assert!(playback.nonogram().is_correct_solution());
+    }
+
+    #[test]
+    fn replay_keeps_the_clue_colors() {
+        let non = colored_nonogram();
+        let moves = vec![
+            ((0, 0), Some(Tile::FilledWithColor('a'))),
+            ((0, 1), Some(Tile::FilledWithColor('b'))),
+        ];
+        let mut playback = Playback::replay(&non, moves);
+        play_to_end(&mut playback);
+        assert_eq!(
+            playback.nonogram().colored_clues(Line::Row(0)),
+            non.colored_clues(Line::Row(0))
+        );
+        assert!(playback.nonogram().is_correct_solution());
+    }
+}
@@ -0,0 +1,193 @@
+//! How tiles and the grid are drawn
+//!
+//! A theme is chosen by name with `--theme` or `theme` in the config file, and
+//! its glyphs can be changed in the config file's `[glyphs]` table.
+
+use cursive::theme::{BaseColor, Color, ColorStyle, PaletteColor};
+
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub filled: String,
+    pub not_filled: String,
+    pub unknown: String,
+    /// Drawn on unknown tiles marked with a dot.
+    pub dot: String,
+    /// Drawn on unknown tiles marked as maybe filled.
+    pub maybe: String,
+    pub top_divider: char,
+    pub side_divider: char,
+    pub corner_divider: char,
+    /// Where guide lines cross.
+    pub cross_divider: char,
+    /// How many terminal columns each tile's glyph takes up.
+    pub cell_width: usize,
+    /// How many blank columns are left between tiles.
+    pub cell_gap: usize,
+    pub filled_style: Option<ColorStyle>,
+    pub not_filled_style: Option<ColorStyle>,
+    pub focus_style: ColorStyle,
+    /// The style of tiles guessed with each pencil, which are drawn with the
+    /// filled and not filled glyphs. There is one pencil per style.
+    pub pencil_styles: Vec<ColorStyle>,
+    /// The style of the clues of the focused row and column.
+    pub clue_focus_style: ColorStyle,
+    /// Whether to highlight the other tiles in the focused row and column.
+    pub crosshair: bool,
+    pub crosshair_style: ColorStyle,
+    /// The style of the line the solver examined in an animation.
+    pub examined_style: ColorStyle,
+    /// The style of the tiles changed in the last frame of an animation.
+    pub changed_style: ColorStyle,
+    /// Draw a guide line between every this many rows and columns.
+    pub guide_every: Option<usize>,
+    /// Number the rows and columns to the right of and below the grid.
+    pub rulers: bool,
+}
+
+impl Theme {
+    pub const NAMES: [&'static str; 5] =
+        ["unicode", "ascii", "blocks", "high-contrast", "colorblind"];
+
+    pub fn named(name: &str) -> Result<Self, String> {
+        match name {
+            "unicode" => Ok(Theme::unicode()),
+            "ascii" => Ok(Theme::ascii()),
+            "blocks" => Ok(Theme::blocks()),
+            "high-contrast" => Ok(Theme::high_contrast()),
+            "colorblind" => Ok(Theme::colorblind()),
+            _ => Err(format!(
+                "unknown theme \"{}\" (expected one of {})",
+                name,
+                Theme::NAMES.join(", ")
+            )),
+        }
+    }
+
+    /// Box drawing characters and ballot boxes.
+    pub fn unicode() -> Self {
+        Theme {
+            filled: "▣".to_string(),
+            not_filled: "☒".to_string(),
+            unknown: "☐".to_string(),
+            dot: "·".to_string(),
+            maybe: "?".to_string(),
+            top_divider: '─',
+            side_divider: '│',
+            corner_divider: '┌',
+            cross_divider: '┼',
+            cell_width: 1,
+            cell_gap: 1,
+            filled_style: None,
+            not_filled_style: None,
+            focus_style: ColorStyle::highlight(),
+            pencil_styles: vec![
+                ColorStyle::tertiary(),
+                ColorStyle::new(Color::Dark(BaseColor::Cyan), PaletteColor::View),
+                ColorStyle::new(Color::Dark(BaseColor::Yellow), PaletteColor::View),
+            ],
+            clue_focus_style: ColorStyle::highlight_inactive(),
+            crosshair: false,
+            crosshair_style: ColorStyle::secondary(),
+            examined_style: ColorStyle::secondary(),
+            changed_style: ColorStyle::highlight(),
+            guide_every: Some(5),
+            rulers: false,
+        }
+    }
+
+    /// Plain ASCII, for terminals and fonts without the Unicode glyphs.
+    pub fn ascii() -> Self {
+        Theme {
+            filled: "#".to_string(),
+            not_filled: "X".to_string(),
+            unknown: "_".to_string(),
+            dot: ".".to_string(),
+            top_divider: '-',
+            side_divider: '|',
+            corner_divider: '+',
+            cross_divider: '+',
+            ..Theme::unicode()
+        }
+    }
+
+    /// Double width full blocks with no gap, so filled tiles join up into the
+    /// picture.
+    pub fn blocks() -> Self {
+        Theme {
+            filled: "██".to_string(),
+            not_filled: "><".to_string(),
+            unknown: "░░".to_string(),
+            cell_width: 2,
+            cell_gap: 0,
+            ..Theme::unicode()
+        }
+    }
+
+    /// Bright filled tiles on black, with a yellow focus.
+    pub fn high_contrast() -> Self {
+        let background = Color::Dark(BaseColor::Black);
+        Theme {
+            filled: "█".to_string(),
+            not_filled: "X".to_string(),
+            unknown: "·".to_string(),
+            dot: "•".to_string(),
+            filled_style: Some(ColorStyle::new(Color::Light(BaseColor::White), background)),
+            not_filled_style: Some(ColorStyle::new(Color::Light(BaseColor::Red), background)),
+            focus_style: ColorStyle::new(background, Color::Light(BaseColor::Yellow)),
+            pencil_styles: vec![
+                ColorStyle::new(Color::Light(BaseColor::Magenta), background),
+                ColorStyle::new(Color::Light(BaseColor::Cyan), background),
+                ColorStyle::new(Color::Light(BaseColor::Green), background),
+            ],
+            clue_focus_style: ColorStyle::new(background, Color::Light(BaseColor::Cyan)),
+            crosshair_style: ColorStyle::new(
+                Color::Light(BaseColor::White),
+                Color::Dark(BaseColor::Blue),
+            ),
+            examined_style: ColorStyle::new(
+                Color::Light(BaseColor::White),
+                Color::Dark(BaseColor::Blue),
+            ),
+            changed_style: ColorStyle::new(background, Color::Light(BaseColor::Yellow)),
+            ..Theme::unicode()
+        }
+    }
+
+    /// Blue and orange from the Okabe-Ito palette instead of colors that are
+    /// told apart by red and green, with distinct glyphs as well.
+    pub fn colorblind() -> Self {
+        let blue = Color::Rgb(0, 114, 178);
+        let orange = Color::Rgb(230, 159, 0);
+        Theme {
+            filled: "■".to_string(),
+            not_filled: "×".to_string(),
+            unknown: "·".to_string(),
+            dot: "•".to_string(),
+            filled_style: Some(ColorStyle::new(blue, PaletteColor::View)),
+            not_filled_style: Some(ColorStyle::new(orange, PaletteColor::View)),
+            focus_style: ColorStyle::new(PaletteColor::View, Color::Rgb(86, 180, 233)),
+            pencil_styles: vec![
+                ColorStyle::new(Color::Rgb(204, 121, 167), PaletteColor::View),
+                ColorStyle::new(Color::Rgb(213, 94, 0), PaletteColor::View),
+                ColorStyle::new(Color::Rgb(0, 158, 115), PaletteColor::View),
+            ],
+            clue_focus_style: ColorStyle::new(PaletteColor::View, Color::Rgb(240, 228, 66)),
+            crosshair_style: ColorStyle::new(blue, Color::Rgb(220, 220, 220)),
+            examined_style: ColorStyle::new(blue, Color::Rgb(220, 220, 220)),
+            changed_style: ColorStyle::new(PaletteColor::View, orange),
+            ..Theme::unicode()
+        }
+    }
+
+    /// How many terminal columns each tile takes up, including the gap after
+    /// it.
+    pub fn cell_stride(&self) -> usize {
+        self.cell_width + self.cell_gap
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::unicode()
+    }
+}
@@ -0,0 +1,131 @@
+//! Player annotations kept apart from a [Nonogram]'s tiles
+//!
+//! Players often want to note more than whether a tile is filled: a dot to
+//! count positions, a mark for a tile that might be filled, or a guess to try
+//! out a hypothesis. Guesses are made with one of several colored pencils, so
+//! that a hypothesis tried on top of another can be told apart from it.
+//! [Annotations] holds these marks in a layer of their own,
+//! so they never change a tile and never count towards
+//! [is_correct_solution](Nonogram::is_correct_solution).
+//!
+//! Guesses can later be committed, which sets each guessed tile in the
+//! [Nonogram], or discarded, which rolls the hypothesis back.
+//!
+//! ```
+//! use nonogram::annotation::{Annotations, Mark};
+//! use nonogram::{Nonogram, Tile};
+//!
+//! let mut non = Nonogram::new(vec![vec![1]], vec![vec![1], vec![]]);
+//! let mut annotations = Annotations::for_nonogram(&non);
+//! annotations.set(0, 0, Mark::Guess(Tile::Filled, 0));
+//! annotations.set(0, 1, Mark::Dot);
+//! assert!(!non.is_correct_solution());
+//!
+//! assert_eq!(annotations.commit_guesses(&mut non).len(), 1);
+//! assert_eq!(non.get_tile(0, 0), Some(Tile::Filled));
+//! assert_eq!(annotations.get(0, 1), Some(Mark::Dot));
+//! ```
+
+use crate::nonogram::{Nonogram, Tile};
+use array2d::Array2D;
+
+/// Which of the player's pencils a guess was made with. How many pencils
+/// there are and what they look like is up to the frontend.
+pub type Pencil = usize;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Mark {
+    /// A plain dot, with whatever meaning the player gives it.
+    Dot,
+    /// A tile that might be filled.
+    Maybe,
+    /// A guess of a tile in a pencil, to commit or discard later.
+    Guess(Tile, Pencil),
+}
+
+impl Mark {
+    pub fn is_guess(self) -> bool {
+        match self {
+            Mark::Guess(..) => true,
+            Mark::Dot | Mark::Maybe => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Annotations {
+    marks: Array2D<Option<Mark>>,
+}
+
+impl Annotations {
+    pub fn new(num_rows: usize, num_cols: usize) -> Self {
+        Annotations {
+            marks: Array2D::filled_with(None, num_rows, num_cols),
+        }
+    }
+
+    /// Empty annotations the size of `non`.
+    pub fn for_nonogram(non: &Nonogram) -> Self {
+        Annotations::new(non.num_rows(), non.num_cols())
+    }
+
+    pub fn get(&self, row: usize, column: usize) -> Option<Mark> {
+        self.marks[(row, column)]
+    }
+
+    pub fn set(&mut self, row: usize, column: usize, mark: Mark) {
+        self.marks[(row, column)] = Some(mark);
+    }
+
+    pub fn unset(&mut self, row: usize, column: usize) {
+        self.marks[(row, column)] = None;
+    }
+
+    /// Set `mark`, or remove it if it is already there.
+    pub fn toggle(&mut self, row: usize, column: usize, mark: Mark) {
+        if self.get(row, column) == Some(mark) {
+            self.unset(row, column);
+        } else {
+            self.set(row, column, mark);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        let num_rows = self.marks.num_rows();
+        let num_cols = self.marks.num_columns();
+        self.marks = Array2D::filled_with(None, num_rows, num_cols);
+    }
+
+    /// The position and guessed tile of every guess, row by row.
+    pub fn guesses(&self) -> Vec<((usize, usize), Tile)> {
+        let mut guesses = Vec::new();
+        for row in 0..self.marks.num_rows() {
+            for column in 0..self.marks.num_columns() {
+                if let Some(Mark::Guess(tile, _)) = self.get(row, column) {
+                    guesses.push(((row, column), tile));
+                }
+            }
+        }
+        guesses
+    }
+
+    /// Set the tile of every guess in `non` and remove the guesses. Returns
+    /// the guesses, row by row.
+    pub fn commit_guesses(&mut self, non: &mut Nonogram) -> Vec<((usize, usize), Tile)> {
+        let guesses = self.guesses();
+        for &((row, column), tile) in &guesses {
+            non.set_tile(row, column, tile);
+            self.unset(row, column);
+        }
+        guesses
+    }
+
+    /// Remove every guess, leaving other marks. Returns how many there were.
+    pub fn discard_guesses(&mut self) -> usize {
+        let guesses = self.guesses();
+        for &((row, column), _) in &guesses {
+            self.unset(row, column);
+        }
+        guesses.len()
+    }
+}
@@ -0,0 +1,463 @@
+//! A compact bitset representation of a [Nonogram] for fast solving
+//!
+//! Each line is stored as two bitsets: one marking which tiles are known, and
+//! one marking which of those are filled. [BitGrid] keeps a copy of every row
+//! and every column in sync, so the line solver can read a whole line without
+//! gathering tiles one at a time. The line solver is the same dynamic
+//! programming as [solver](crate::solver), but steps a whole clue at a time:
+//! each step is a handful of shifts and masks over the words of the line
+//! rather than a loop over its tiles.
+//!
+//! Colors are not stored, so converting a [Nonogram] with
+//! [FilledWithColor](Tile::FilledWithColor) tiles into a [BitGrid] and back
+//! turns them into plain [Filled](Tile::Filled) tiles, and the colors of
+//! colored clues are ignored.
+
+use crate::nonogram::{Clue, Line, LineClues, MaybeTile, Nonogram, Tile};
+use crate::solver::{Contradiction, LineQueue, Status};
+
+const WORD_BITS: usize = 64;
+
+/// A fixed-length set of bits.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct BitLine {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitLine {
+    /// Create a [BitLine] of `len` bits, all unset.
+    pub fn new(len: usize) -> Self {
+        BitLine {
+            words: vec![0; (len + WORD_BITS - 1) / WORD_BITS],
+            len,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, index: usize) -> bool {
+        assert!(index < self.len, "bit index out of bounds");
+        self.words[index / WORD_BITS] & (1 << (index % WORD_BITS)) != 0
+    }
+
+    pub fn set(&mut self, index: usize, value: bool) {
+        assert!(index < self.len, "bit index out of bounds");
+        let mask = 1 << (index % WORD_BITS);
+        if value {
+            self.words[index / WORD_BITS] |= mask;
+        } else {
+            self.words[index / WORD_BITS] &= !mask;
+        }
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// The index of the first set bit at or after `from`, or the length if
+    /// there is none.
+    pub fn next_set(&self, from: usize) -> usize {
+        if from >= self.len {
+            return self.len;
+        }
+        let mut word_index = from / WORD_BITS;
+        let mut word = self.words[word_index] & (!0 << (from % WORD_BITS));
+        loop {
+            if word != 0 {
+                let index = word_index * WORD_BITS + word.trailing_zeros() as usize;
+                return index.min(self.len);
+            }
+            word_index += 1;
+            if word_index == self.words.len() {
+                return self.len;
+            }
+            word = self.words[word_index];
+        }
+    }
+
+    /// The bits set in `self` but not in `other`.
+    pub fn and_not(&self, other: &BitLine) -> BitLine {
+        self.zip_with(other, |a, b| a & !b)
+    }
+
+    pub fn and(&self, other: &BitLine) -> BitLine {
+        self.zip_with(other, |a, b| a & b)
+    }
+
+    pub fn or(&self, other: &BitLine) -> BitLine {
+        self.zip_with(other, |a, b| a | b)
+    }
+
+    /// The bits of `self` moved `shift` places toward higher indices.
+    fn shifted_up(&self, shift: usize) -> BitLine {
+        let mut shifted = BitLine::new(self.len);
+        let (words, bits) = (shift / WORD_BITS, shift % WORD_BITS);
+        for index in words..self.words.len() {
+            let mut word = self.words[index - words] << bits;
+            if bits > 0 && index > words {
+                word |= self.words[index - words - 1] >> (WORD_BITS - bits);
+            }
+            shifted.words[index] = word;
+        }
+        shifted.clear_tail();
+        shifted
+    }
+
+    /// The bits of `self` moved `shift` places toward lower indices.
+    fn shifted_down(&self, shift: usize) -> BitLine {
+        let mut shifted = BitLine::new(self.len);
+        let (words, bits) = (shift / WORD_BITS, shift % WORD_BITS);
+        for index in 0..self.words.len().saturating_sub(words) {
+            let mut word = self.words[index + words] >> bits;
+            if bits > 0 && index + words + 1 < self.words.len() {
+                word |= self.words[index + words + 1] << (WORD_BITS - bits);
+            }
+            shifted.words[index] = word;
+        }
+        shifted
+    }
+
+    /// The bits not set in `self`.
+    fn not(&self) -> BitLine {
+        let mut inverse = BitLine {
+            words: self.words.iter().map(|word| !word).collect(),
+            len: self.len,
+        };
+        inverse.clear_tail();
+        inverse
+    }
+
+    /// The positions `index` where every bit in `index..index + run` is set.
+    fn eroded(&self, run: usize) -> BitLine {
+        let mut eroded = self.clone();
+        let mut covered = 1;
+        while covered < run {
+            let step = covered.min(run - covered);
+            eroded = eroded.and(&eroded.shifted_down(step));
+            covered += step;
+        }
+        eroded
+    }
+
+    /// Every bit within `run` places at or after a set bit of `self`.
+    fn dilated(&self, run: usize) -> BitLine {
+        let mut dilated = self.clone();
+        let mut covered = 1;
+        while covered < run {
+            let step = covered.min(run - covered);
+            dilated = dilated.or(&dilated.shifted_up(step));
+            covered += step;
+        }
+        dilated
+    }
+
+    /// `self` plus every bit reachable by stepping up from a set bit through
+    /// bits set in `through`.
+    fn smeared_up(&self, through: &BitLine) -> BitLine {
+        let (mut smeared, mut passes, mut shift) = (self.clone(), through.clone(), 1);
+        while shift < self.len {
+            smeared = smeared.or(&smeared.and(&passes).shifted_up(shift));
+            passes = passes.and(&passes.shifted_down(shift));
+            shift *= 2;
+        }
+        smeared
+    }
+
+    /// `self` plus every bit set in `through` from which stepping up through
+    /// bits set in `through` reaches a set bit.
+    fn smeared_down(&self, through: &BitLine) -> BitLine {
+        let (mut smeared, mut passes, mut shift) = (self.clone(), through.clone(), 1);
+        while shift < self.len {
+            smeared = smeared.or(&passes.and(&smeared.shifted_down(shift)));
+            passes = passes.and(&passes.shifted_down(shift));
+            shift *= 2;
+        }
+        smeared
+    }
+
+    /// A copy of `self` with one more, unset, bit.
+    fn widened(&self) -> BitLine {
+        let mut wide = BitLine::new(self.len + 1);
+        wide.words[..self.words.len()].copy_from_slice(&self.words);
+        wide
+    }
+
+    /// A copy of `self` without its last bit.
+    fn narrowed(&self) -> BitLine {
+        let mut narrow = BitLine::new(self.len - 1);
+        let num_words = narrow.words.len();
+        narrow.words.copy_from_slice(&self.words[..num_words]);
+        narrow.clear_tail();
+        narrow
+    }
+
+    /// Unset the unused bits of the last word.
+    fn clear_tail(&mut self) {
+        if self.len % WORD_BITS != 0 {
+            if let Some(last) = self.words.last_mut() {
+                *last &= !0 >> (WORD_BITS - self.len % WORD_BITS);
+            }
+        }
+    }
+
+    fn zip_with<F>(&self, other: &BitLine, f: F) -> BitLine
+    where
+        F: Fn(u64, u64) -> u64,
+    {
+        assert_eq!(self.len, other.len, "bit lines must be the same length");
+        let words = self
+            .words
+            .iter()
+            .zip(other.words.iter())
+            .map(|(&a, &b)| f(a, b))
+            .collect();
+        BitLine {
+            words,
+            len: self.len,
+        }
+    }
+}
+
+/// The known tiles of a single line. A tile is unknown unless its bit is set
+/// in `known`, and filled if its bit is also set in `filled`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct LineBits {
+    pub known: BitLine,
+    pub filled: BitLine,
+}
+
+impl LineBits {
+    /// Create a [LineBits] of `len` unknown tiles.
+    pub fn new(len: usize) -> Self {
+        LineBits {
+            known: BitLine::new(len),
+            filled: BitLine::new(len),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.known.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.known.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> MaybeTile {
+        if !self.known.get(index) {
+            None
+        } else if self.filled.get(index) {
+            Some(Tile::Filled)
+        } else {
+            Some(Tile::NotFilled)
+        }
+    }
+
+    pub fn set(&mut self, index: usize, maybe_tile: MaybeTile) {
+        self.known.set(index, maybe_tile.is_some());
+        self.filled
+            .set(index, matches!(maybe_tile, Some(tile) if tile.is_filled()));
+    }
+
+    /// The tiles that are known to be crossed out.
+    pub fn not_filled(&self) -> BitLine {
+        self.known.and_not(&self.filled)
+    }
+}
+
+/// Deduce every tile of a single line that is the same in all arrangements of
+/// `clues` consistent with the known tiles of `line`.
+///
+/// Returns [None] if no arrangement is consistent with `line`.
+pub fn solve_line_bits(clues: &[Clue], line: &LineBits) -> Option<LineBits> {
+    let lengths: Vec<Clue> = clues.iter().cloned().filter(|&clue| clue > 0).collect();
+    let len = line.len();
+    let num_blocks = lengths.len();
+
+    // The sets below have one bit per tile and one more for the end of the
+    // line, and are built a whole clue at a time from shifts and masks.
+    let mut end = BitLine::new(len + 1);
+    end.set(len, true);
+    let gap = line.known.and(&line.filled).widened().not().and_not(&end);
+    let open = line.not_filled().widened().not().and_not(&end);
+
+    // starts[j]: where block j fits between crossed out tiles, with a tile
+    // that may be empty or the end of the line right after it.
+    let starts: Vec<BitLine> = lengths
+        .iter()
+        .map(|&length| open.eroded(length).and(&gap.or(&end).shifted_down(length)))
+        .collect();
+    // Where the next block may begin after one starting at `from`.
+    let after = |from: &BitLine, length: usize| {
+        let mut next = from.shifted_up(length + 1);
+        if length <= len && from.get(len - length) {
+            next.set(len, true);
+        }
+        next
+    };
+    // Where a block may start so that the next block may begin at `to`.
+    let before = |to: &BitLine, length: usize| {
+        let mut start = to.shifted_down(length + 1);
+        if length <= len && to.get(len) {
+            start.set(len - length, true);
+        }
+        start
+    };
+
+    // prefix[j]: positions before which the tiles can hold exactly the first
+    // j blocks. suffix[j]: positions from which they can hold blocks j on.
+    let mut prefix = Vec::with_capacity(num_blocks + 1);
+    let mut reached = BitLine::new(len + 1);
+    reached.set(0, true);
+    for (block, &length) in lengths.iter().enumerate() {
+        let reached_here = reached.smeared_up(&gap);
+        reached = after(&reached_here.and(&starts[block]), length);
+        prefix.push(reached_here);
+    }
+    prefix.push(reached.smeared_up(&gap));
+    if !prefix[num_blocks].get(len) {
+        return None;
+    }
+
+    let mut suffix = vec![BitLine::new(len + 1); num_blocks + 1];
+    suffix[num_blocks] = end.smeared_down(&gap);
+    for (block, &length) in lengths.iter().enumerate().rev() {
+        suffix[block] = before(&suffix[block + 1], length)
+            .and(&starts[block])
+            .smeared_down(&gap);
+    }
+
+    let mut can_be_filled = BitLine::new(len + 1);
+    let mut can_be_empty = BitLine::new(len + 1);
+    for block in 0..=num_blocks {
+        let between = prefix[block].and(&gap).and(&suffix[block].shifted_down(1));
+        can_be_empty = can_be_empty.or(&between);
+    }
+    for (block, &length) in lengths.iter().enumerate() {
+        let placed = prefix[block]
+            .and(&starts[block])
+            .and(&before(&suffix[block + 1], length));
+        can_be_filled = can_be_filled.or(&placed.dilated(length));
+        can_be_empty = can_be_empty.or(&placed.shifted_up(length).and(&gap));
+    }
+
+    let only_filled = can_be_filled.and_not(&can_be_empty).narrowed();
+    let only_empty = can_be_empty.and_not(&can_be_filled).narrowed();
+    Some(LineBits {
+        known: line.known.or(&only_filled).or(&only_empty),
+        filled: line.filled.or(&only_filled),
+    })
+}
+
+/// A [Nonogram] stored as [LineBits] for every row and every column.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BitGrid {
+    pub(crate) rows: Vec<LineBits>,
+    pub(crate) columns: Vec<LineBits>,
+    pub(crate) row_clues: Vec<LineClues>,
+    pub(crate) column_clues: Vec<LineClues>,
+}
+
+impl BitGrid {
+    pub fn num_rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn num_cols(&self) -> usize {
+        self.columns.len()
+    }
+
+    pub fn line(&self, line: Line) -> &LineBits {
+        match line {
+            Line::Row(index) => &self.rows[index],
+            Line::Column(index) => &self.columns[index],
+        }
+    }
+
+    pub fn get_tile(&self, row: usize, column: usize) -> MaybeTile {
+        self.rows[row].get(column)
+    }
+
+    /// Set a tile in both its row and its column.
+    pub fn set_tile(&mut self, row: usize, column: usize, maybe_tile: MaybeTile) {
+        self.rows[row].set(column, maybe_tile);
+        self.columns[column].set(row, maybe_tile);
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.rows
+            .iter()
+            .all(|row| row.known.count_ones() == row.len())
+    }
+
+    /// Apply line logic to every row and column until no more progress can be
+    /// made, like [solver::solve](crate::solver::solve).
+    pub fn solve(&mut self) -> Result<Status, Contradiction> {
+        let mut queue = LineQueue::with_dimensions(self.num_rows(), self.num_cols());
+        while let Some((line, sweep)) = queue.pop() {
+            let (clues, old) = match line {
+                Line::Row(index) => (&self.row_clues[index], &self.rows[index]),
+                Line::Column(index) => (&self.column_clues[index], &self.columns[index]),
+            };
+            let new = solve_line_bits(clues, old).ok_or(Contradiction { line })?;
+            let newly_known = new.known.and_not(&old.known);
+            let mut index = newly_known.next_set(0);
+            while index < newly_known.len() {
+                let (row, column) = line.position(index);
+                self.set_tile(row, column, new.get(index));
+                queue.push(line.crossing(index), sweep + 1);
+                index = newly_known.next_set(index + 1);
+            }
+        }
+        if self.is_complete() {
+            Ok(Status::Solved)
+        } else {
+            Ok(Status::Stalled)
+        }
+    }
+
+    pub fn to_nonogram(&self) -> Nonogram {
+        let mut non = Nonogram::new(self.row_clues.clone(), self.column_clues.clone());
+        for (row_index, row) in self.rows.iter().enumerate() {
+            for column_index in 0..row.len() {
+                if let Some(tile) = row.get(column_index) {
+                    non.set_tile(row_index, column_index, tile);
+                }
+            }
+        }
+        non
+    }
+}
+
+impl From<&Nonogram> for BitGrid {
+    fn from(non: &Nonogram) -> Self {
+        let mut grid = BitGrid {
+            rows: vec![LineBits::new(non.num_cols()); non.num_rows()],
+            columns: vec![LineBits::new(non.num_rows()); non.num_cols()],
+            row_clues: non.row_clues().to_vec(),
+            column_clues: non.column_clues().to_vec(),
+        };
+        for row in 0..non.num_rows() {
+            for column in 0..non.num_cols() {
+                grid.set_tile(row, column, non.get_tile(row, column));
+            }
+        }
+        grid
+    }
+}
+
+impl From<&BitGrid> for Nonogram {
+    fn from(grid: &BitGrid) -> Self {
+        grid.to_nonogram()
+    }
+}
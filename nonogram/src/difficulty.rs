@@ -0,0 +1,122 @@
+//! Estimating how hard a [Nonogram] is to solve
+//!
+//! A puzzle is rated by solving it and measuring how much effort that took.
+//! Puzzles that line logic can finish are rated by their size and the number of
+//! sweeps line logic needed, since each sweep is roughly one more pass a person
+//! has to make over the grid. Puzzles that need probing are rated at least
+//! [Hard](Category::Hard), and puzzles that need guessing are always rated
+//! hardest, with the score growing with the number of probes and the number
+//! and depth of the guesses.
+
+use crate::nonogram::Nonogram;
+use crate::solver::{self, Status};
+use std::fmt;
+
+/// A rough label for a puzzle's difficulty, from easiest to hardest.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Category {
+    Easy,
+    Medium,
+    Hard,
+    RequiresGuessing,
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Category::Easy => "easy",
+            Category::Medium => "medium",
+            Category::Hard => "hard",
+            Category::RequiresGuessing => "requires guessing",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Difficulty {
+    /// Higher is harder. Only meaningful relative to other scores.
+    pub score: f64,
+    pub category: Category,
+    /// The number of line logic sweeps made before solving finished or
+    /// stalled.
+    pub sweeps: usize,
+    /// Whether line logic alone stalled before finishing the puzzle.
+    pub needs_probing: bool,
+    /// The number of tiles probed, or zero if no probing was needed.
+    pub probes: usize,
+    /// Whether probing stalled before finishing the puzzle.
+    pub needs_backtracking: bool,
+    /// The most guesses that had to be in effect at once, or zero if no
+    /// guessing was needed.
+    pub branching_depth: usize,
+    /// The total number of guesses made while backtracking.
+    pub guesses: usize,
+    /// Whether the puzzle has exactly one solution.
+    pub is_unique: bool,
+    pub num_tiles: usize,
+}
+
+impl Difficulty {
+    const MEDIUM_SCORE: f64 = 25.0;
+    const HARD_SCORE: f64 = 50.0;
+    const SWEEP_WEIGHT: f64 = 2.0;
+    const PROBING_SCORE: f64 = 20.0;
+    const DEPTH_WEIGHT: f64 = 25.0;
+}
+
+/// Rate how hard `non` is to solve from its current state, or [None] if it
+/// has no solution.
+pub fn rate(non: &Nonogram) -> Option<Difficulty> {
+    let mut attempt = non.clone();
+    let (mut status, effort) = solver::solve_with_effort(&mut attempt).ok()?;
+    let num_tiles = non.num_rows() * non.num_cols();
+    let mut score = (num_tiles as f64).sqrt() + Difficulty::SWEEP_WEIGHT * effort.sweeps as f64;
+
+    let needs_probing = status == Status::Stalled;
+    let mut probes = 0;
+    if needs_probing {
+        let (probed_status, probe_effort) = solver::solve_with_probing(&mut attempt).ok()?;
+        status = probed_status;
+        probes = probe_effort.probes;
+        score += Difficulty::PROBING_SCORE + probes as f64;
+    }
+
+    let (is_unique, branching_depth, guesses) = match status {
+        Status::Solved => (true, 0, 0),
+        Status::Stalled => {
+            let search = solver::search(&attempt, 2);
+            if search.solutions.is_empty() {
+                return None;
+            }
+            (search.solutions.len() == 1, search.max_depth, search.guesses)
+        }
+    };
+    let needs_backtracking = status == Status::Stalled;
+    score += Difficulty::DEPTH_WEIGHT * branching_depth as f64 + guesses as f64;
+
+    let category = if needs_backtracking {
+        Category::RequiresGuessing
+    } else if needs_probing {
+        Category::Hard
+    } else if score < Difficulty::MEDIUM_SCORE {
+        Category::Easy
+    } else if score < Difficulty::HARD_SCORE {
+        Category::Medium
+    } else {
+        Category::Hard
+    };
+
+    Some(Difficulty {
+        score,
+        category,
+        sweeps: effort.sweeps,
+        needs_probing,
+        probes,
+        needs_backtracking,
+        branching_depth,
+        guesses,
+        is_unique,
+        num_tiles,
+    })
+}
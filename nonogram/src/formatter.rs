@@ -1,4 +1,7 @@
 use crate::nonogram::{LineClues, MaybeTile, Nonogram, Tile};
+use crate::nonogram_file::{Color, ColorCode, ColorDefinition};
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::fmt::Display;
 
 pub fn main() {
@@ -22,11 +25,38 @@ pub fn main() {
 
 type Grid<T> = Vec<Vec<T>>;
 
+/// The ANSI escape codes [Formatter] uses to draw tiles filled with a color.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ColorMode {
+    /// 24-bit colors, exactly as defined.
+    TrueColor,
+    /// The nearest of the 256 colors most terminals support.
+    Ansi256,
+}
+
+impl ColorMode {
+    /// The escape code that sets the foreground to `color`.
+    pub fn escape_code(self, color: Color) -> String {
+        let Color { red, green, blue } = color;
+        match self {
+            ColorMode::TrueColor => format!("\x1b[38;2;{};{};{}m", red, green, blue),
+            ColorMode::Ansi256 => format!("\x1b[38;5;{}m", ansi256(color)),
+        }
+    }
+}
+
+/// Resets the colors set by [ColorMode::escape_code].
+const RESET: &str = "\x1b[0m";
+
 pub struct Formatter {
     filled_string: String,
     not_filled_string: String,
     none_string: String,
     do_display_numbers: bool,
+    guide_every: Option<usize>,
+    do_display_rulers: bool,
+    color_mode: Option<ColorMode>,
+    palette: HashMap<char, Color>,
 }
 
 impl Formatter {
@@ -41,9 +71,43 @@ impl Formatter {
             not_filled_string: not_filled_string.to_string(),
             none_string: none_string.to_string(),
             do_display_numbers,
+            guide_every: None,
+            do_display_rulers: false,
+            color_mode: None,
+            palette: HashMap::new(),
         }
     }
 
+    /// Draw a major guide line between every `every` rows and columns, like
+    /// the thick lines of a printed puzzle. Guides are turned off with 0.
+    pub fn with_guides(mut self, every: usize) -> Self {
+        self.guide_every = if every == 0 { None } else { Some(every) };
+        self
+    }
+
+    /// Number the first row and column and every fifth one, or every guide
+    /// if guides are on, to the right of and below the grid.
+    pub fn with_rulers(mut self, do_display_rulers: bool) -> Self {
+        self.do_display_rulers = do_display_rulers;
+        self
+    }
+
+    /// Draw tiles filled with a color in that color, using the colors defined
+    /// by `colors`, such as those of a
+    /// [NonogramFile](crate::nonogram_file::NonogramFile). Tiles with a color
+    /// that isn't defined are drawn like other filled tiles.
+    pub fn with_colors(mut self, mode: ColorMode, colors: &[ColorDefinition]) -> Self {
+        self.color_mode = Some(mode);
+        self.palette = colors
+            .iter()
+            .map(|definition| {
+                let ColorCode(code) = definition.code;
+                (code, definition.color)
+            })
+            .collect();
+        self
+    }
+
     pub fn get_string_grid(&self, non: &Nonogram) -> Grid<String> {
         let rows_string_grid = Formatter::get_rows_clue_string_grid(non);
         let cols_string_grid = Formatter::get_cols_clue_string_grid(non);
@@ -61,12 +125,12 @@ impl Formatter {
             .collect();
 
         let mut bottom_section = rows_string_grid;
-        for (row_clues, cells) in bottom_section.iter_mut().zip(cells_string_grid.into_iter()) {
+        for (row_clues, cells) in bottom_section.iter_mut().zip(cells_string_grid) {
             row_clues.extend(cells);
         }
 
         let mut string_grid = top_section;
-        string_grid.extend(bottom_section.into_iter());
+        string_grid.extend(bottom_section);
 
         string_grid
     }
@@ -102,7 +166,7 @@ impl Formatter {
         non.rows()
             .map(|row_iter| {
                 row_iter
-                    .map(|maybe_tile| self.format_tile(maybe_tile.clone()).to_string())
+                    .map(|maybe_tile| self.format_tile(*maybe_tile).to_string())
                     .collect()
             })
             .collect()
@@ -135,10 +199,10 @@ impl Formatter {
     }
 
     pub fn get_lines(&self, non: &Nonogram) -> Vec<String> {
-        let grid_lines = self.get_only_grid_lines(non);
         if !self.do_display_numbers {
-            return grid_lines;
+            return self.get_only_grid_lines(non);
         }
+        let grid_lines = self.get_ruled_grid_lines(non);
         let row_lines = self.get_row_clue_lines(non);
         let column_lines = self.get_column_clue_lines(non);
         let max_row_width = row_lines[0].len();
@@ -151,26 +215,135 @@ impl Formatter {
         let row_and_grid_lines = row_lines
             .iter()
             .zip(grid_lines.iter())
-            .map(|(row_line, grid_line)| format!("{} |{}", row_line, grid_line));
+            .map(|(row_line, grid_line)| format!("{} |{}", row_line, grid_line))
+            .collect();
+        let guide_line = format!("{} |{}", leading_spaces, self.get_guide_line(non));
+        let column_ruler = self
+            .get_column_ruler(non)
+            .map(|ruler| format!("{}  {}", leading_spaces, ruler));
         column_lines_with_leading_spaces
             .chain(Some(horizontal_line))
-            .chain(row_and_grid_lines)
+            .chain(self.insert_guide_lines(row_and_grid_lines, &guide_line))
+            .chain(column_ruler)
             .collect()
     }
 
     pub fn get_only_grid_lines(&self, non: &Nonogram) -> Vec<String> {
-        // non.rows().map(|row| row.iter().map(|maybe_tile| self.format_tile(*maybe_tile)))
+        let grid_lines = self.get_ruled_grid_lines(non);
+        let mut lines = self.insert_guide_lines(grid_lines, &self.get_guide_line(non));
+        lines.extend(self.get_column_ruler(non));
+        lines
+    }
+
+    /// The line of each row, numbered on the right if rulers are on.
+    fn get_ruled_grid_lines(&self, non: &Nonogram) -> Vec<String> {
+        let label_width = non.num_rows().to_string().len();
         (0..non.num_rows())
-            .map(|row_index| self.get_grid_line(non, row_index))
+            .map(|row_index| {
+                let line = self.get_grid_line(non, row_index);
+                match self.ruler_label(row_index) {
+                    Some(label) => format!("{} {:>width$}", line, label, width = label_width),
+                    None => line,
+                }
+            })
             .collect()
     }
 
     fn get_grid_line(&self, non: &Nonogram, index: usize) -> String {
         let tile_strings: Vec<_> = non
             .get_row(index)
-            .map(|maybe_tile| self.format_tile(*maybe_tile))
+            .map(|maybe_tile| self.format_colored_tile(*maybe_tile))
             .collect();
-        tile_strings.join(" ")
+        self.join_cells(tile_strings.iter().map(|s| s.as_ref()), ' ', '|')
+    }
+
+    /// The line drawn between rows at a guide.
+    fn get_guide_line(&self, non: &Nonogram) -> String {
+        let dashes = "-".repeat(self.tile_width());
+        let cells = (0..non.num_cols()).map(|_| dashes.as_str());
+        self.join_cells(cells, '-', '+')
+    }
+
+    /// Join the strings of each cell in a line with `separator`, or `guide`
+    /// where there is a guide.
+    fn join_cells<'a>(
+        &self,
+        cells: impl Iterator<Item = &'a str>,
+        separator: char,
+        guide: char,
+    ) -> String {
+        let mut line = String::new();
+        for (index, cell) in cells.enumerate() {
+            if index > 0 && self.is_guide_at(index) {
+                line.push(guide);
+            } else if index > 0 {
+                line.push(separator);
+            }
+            line.push_str(cell);
+        }
+        line
+    }
+
+    /// Put `guide_line` before every row that starts a new group of guides.
+    fn insert_guide_lines(&self, lines: Vec<String>, guide_line: &str) -> Vec<String> {
+        let mut guided = Vec::with_capacity(lines.len());
+        for (index, line) in lines.into_iter().enumerate() {
+            if index > 0 && self.is_guide_at(index) {
+                guided.push(guide_line.to_string());
+            }
+            guided.push(line);
+        }
+        guided
+    }
+
+    /// The column numbers, each starting below its column, if rulers are on.
+    fn get_column_ruler(&self, non: &Nonogram) -> Option<String> {
+        if !self.do_display_rulers {
+            return None;
+        }
+        let mut ruler = String::new();
+        let mut x = 0;
+        for index in 0..non.num_cols() {
+            if let Some(label) = self.ruler_label(index) {
+                // Labels need a space between them
+                if ruler.is_empty() || ruler.len() < x {
+                    ruler.push_str(&" ".repeat(x - ruler.len()));
+                    ruler.push_str(&label.to_string());
+                }
+            }
+            x += self.tile_width() + 1;
+        }
+        Some(ruler)
+    }
+
+    /// The 1-based number of the line at `index`, if it's on the ruler.
+    fn ruler_label(&self, index: usize) -> Option<usize> {
+        let step = self.guide_every.unwrap_or(5);
+        if self.do_display_rulers && (index == 0 || (index + 1) % step == 0) {
+            Some(index + 1)
+        } else {
+            None
+        }
+    }
+
+    fn is_guide_at(&self, index: usize) -> bool {
+        match self.guide_every {
+            Some(every) => index % every == 0,
+            None => false,
+        }
+    }
+
+    /// The number of characters in the widest tile string.
+    fn tile_width(&self) -> usize {
+        [
+            &self.filled_string,
+            &self.not_filled_string,
+            &self.none_string,
+        ]
+        .iter()
+        .map(|s| s.chars().count())
+        .max()
+        .unwrap()
     }
 
     fn format_tile(&self, maybe_tile: MaybeTile) -> &str {
@@ -182,6 +355,19 @@ impl Formatter {
         }
     }
 
+    /// The tile's string, wrapped in escape codes for its color if it has
+    /// one and colors are on.
+    fn format_colored_tile(&self, maybe_tile: MaybeTile) -> Cow<'_, str> {
+        let s = self.format_tile(maybe_tile);
+        match (maybe_tile, self.color_mode) {
+            (Some(Tile::FilledWithColor(code)), Some(mode)) => match self.palette.get(&code) {
+                Some(&color) => Cow::Owned(format!("{}{}{}", mode.escape_code(color), s, RESET)),
+                None => Cow::Borrowed(s),
+            },
+            _ => Cow::Borrowed(s),
+        }
+    }
+
     fn get_row_clue_lines(&self, non: &Nonogram) -> Vec<String> {
         let max_num_clues = non.row_clues().iter().map(Vec::len).max().unwrap();
         let clue_strings: Grid<_> = non
@@ -285,10 +471,50 @@ impl Default for Formatter {
             not_filled_string: "x".to_string(),
             none_string: "_".to_string(),
             do_display_numbers: true,
+            guide_every: None,
+            do_display_rulers: false,
+            color_mode: None,
+            palette: HashMap::new(),
         }
     }
 }
 
+/// The index of the nearest of the 256 terminal colors to `color`, from the
+/// 6x6x6 color cube or the 24 step gray ramp. The first 16 are left out, as
+/// terminals often change them.
+fn ansi256(color: Color) -> u8 {
+    const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let nearest_level = |value: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&i| (i32::from(CUBE_LEVELS[i]) - i32::from(value)).abs())
+            .unwrap()
+    };
+    let distance = |(red, green, blue): (u8, u8, u8)| {
+        let d = |a: u8, b: u8| (i32::from(a) - i32::from(b)).pow(2);
+        d(red, color.red) + d(green, color.green) + d(blue, color.blue)
+    };
+
+    let (r, g, b) = (
+        nearest_level(color.red),
+        nearest_level(color.green),
+        nearest_level(color.blue),
+    );
+    let cube_index = 16 + 36 * r + 6 * g + b;
+    let cube_distance = distance((CUBE_LEVELS[r], CUBE_LEVELS[g], CUBE_LEVELS[b]));
+
+    // The gray ramp goes from 8 to 238 in steps of 10
+    let average = (u32::from(color.red) + u32::from(color.green) + u32::from(color.blue)) / 3;
+    let gray_step = (average.saturating_sub(3) / 10).min(23) as u8;
+    let gray = 8 + 10 * gray_step;
+    let gray_distance = distance((gray, gray, gray));
+
+    if gray_distance < cube_distance {
+        232 + gray_step
+    } else {
+        cube_index as u8
+    }
+}
+
 fn get_max_num_row_clues(nonogram: &Nonogram) -> usize {
     get_max_num_clues(nonogram.row_clues())
 }
@@ -0,0 +1,149 @@
+//! Generating random [Nonogram] puzzles with a unique solution
+//!
+//! A random grid is filled in with the requested density and its clues are
+//! derived. The empty puzzle is then solved with line logic. If solving stalls,
+//! the solution is not unique (or at least cannot be found without guessing),
+//! so one of the tiles that was left unknown is flipped in the grid and the
+//! process starts over with the new clues. This repeats until line logic alone
+//! solves the puzzle, which guarantees it has exactly one solution.
+//!
+//! A maximum [Category] can be requested instead. Then probing is used as well
+//! as line logic if [Hard](Category::Hard) puzzles are allowed, and if guessing
+//! is allowed a stalled puzzle is accepted as soon as backtracking shows its
+//! solution is unique. Puzzles that are solved but rated too hard are thrown
+//! away for a fresh grid.
+
+use crate::difficulty::{self, Category};
+use crate::nonogram::{Nonogram, Tile};
+use crate::solver::{self, Status};
+use rand::Rng;
+
+/// A generated puzzle along with its solution.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct GeneratedPuzzle {
+    /// The puzzle with every tile unknown.
+    pub puzzle: Nonogram,
+    /// The same clues with every tile set to the unique solution.
+    pub solution: Nonogram,
+}
+
+#[derive(Debug, Clone)]
+pub struct Generator {
+    num_rows: usize,
+    num_cols: usize,
+    density: f64,
+    max_category: Option<Category>,
+    max_tweaks: usize,
+    max_attempts: usize,
+}
+
+impl Generator {
+    const DEFAULT_DENSITY: f64 = 0.5;
+
+    pub fn new(num_rows: usize, num_cols: usize) -> Self {
+        Generator {
+            num_rows,
+            num_cols,
+            density: Generator::DEFAULT_DENSITY,
+            max_category: None,
+            max_tweaks: num_rows * num_cols,
+            max_attempts: 100,
+        }
+    }
+
+    /// The chance of each tile being filled in the initial random grid.
+    /// Tweaking the grid to make the solution unique changes this slightly.
+    /// It is clamped between 0 and 1, and NaN or an infinity leaves the
+    /// default of 0.5.
+    pub fn density(mut self, density: f64) -> Self {
+        if density.is_finite() {
+            self.density = density.clamp(0.0, 1.0);
+        }
+        self
+    }
+
+    /// The hardest puzzles to generate. By default, any puzzle that line
+    /// logic alone can solve is allowed, no matter how it is rated.
+    pub fn max_difficulty(mut self, max_category: Category) -> Self {
+        self.max_category = Some(max_category);
+        self
+    }
+
+    /// How many tiles may be flipped in a single random grid before giving up
+    /// on it and starting again with a fresh grid.
+    pub fn max_tweaks(mut self, max_tweaks: usize) -> Self {
+        self.max_tweaks = max_tweaks;
+        self
+    }
+
+    /// How many fresh random grids to try before giving up entirely.
+    pub fn max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Generate a puzzle with a unique solution that is no harder than the
+    /// maximum difficulty, or [None] if every attempt failed.
+    pub fn generate<R: Rng>(&self, rng: &mut R) -> Option<GeneratedPuzzle> {
+        (0..self.max_attempts).find_map(|_| self.try_generate(rng))
+    }
+
+    fn try_generate<R: Rng>(&self, rng: &mut R) -> Option<GeneratedPuzzle> {
+        let mut grid = self.random_grid(rng);
+        for _ in 0..=self.max_tweaks {
+            let solution = Nonogram::from_solution(&grid);
+            let puzzle = Nonogram::new(
+                solution.row_clues().to_vec(),
+                solution.column_clues().to_vec(),
+            );
+            let mut attempt = puzzle.clone();
+            // The clues came from a real grid, so they can never contradict.
+            let mut status = solver::solve(&mut attempt).ok()?;
+            let is_acceptable = match self.max_category {
+                None => status == Status::Solved,
+                Some(max_category) => {
+                    if status == Status::Stalled && max_category >= Category::Hard {
+                        status = solver::solve_with_probing(&mut attempt).ok()?.0;
+                    }
+                    let is_unique = status == Status::Solved
+                        || (max_category == Category::RequiresGuessing
+                            && solver::search(&attempt, 2).solutions.len() == 1);
+                    is_unique
+                        && difficulty::rate(&puzzle)
+                            .filter(|rating| rating.category <= max_category)
+                            .is_some()
+                }
+            };
+            if is_acceptable {
+                return Some(GeneratedPuzzle { puzzle, solution });
+            }
+            if status == Status::Solved {
+                // Flipping tiles is unlikely to make the puzzle easier.
+                return None;
+            }
+            let unknown: Vec<_> = solver::unknown_positions(&attempt).collect();
+            let (row, column) = unknown[rng.gen_range(0, unknown.len())];
+            grid[row][column] = match grid[row][column] {
+                Tile::NotFilled => Tile::Filled,
+                _ => Tile::NotFilled,
+            };
+        }
+        None
+    }
+
+    fn random_grid<R: Rng>(&self, rng: &mut R) -> Vec<Vec<Tile>> {
+        (0..self.num_rows)
+            .map(|_| {
+                (0..self.num_cols)
+                    .map(|_| {
+                        if rng.gen_bool(self.density) {
+                            Tile::Filled
+                        } else {
+                            Tile::NotFilled
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
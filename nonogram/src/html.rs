@@ -0,0 +1,257 @@
+//! Standalone HTML pages of puzzles, for printing and the web
+//!
+//! [HtmlRenderer] writes a [NonogramFile] as a complete page: its title and
+//! author, the puzzle as a table with the clues in header cells, and its
+//! copyright and license. The page needs no scripts or external stylesheets,
+//! and prints with each puzzle and solution on a page of its own.
+//!
+//! ```
+//! use nonogram::html::HtmlRenderer;
+//! use nonogram::nonogram_file::{Hint, NonogramFile};
+//!
+//! let hint = |length| Hint { length, color: None };
+//! let file = NonogramFile {
+//!     width: 2,
+//!     height: 1,
+//!     rows: vec![vec![hint(1)]],
+//!     columns: vec![vec![hint(1)], vec![]],
+//!     goal: None,
+//!     catalogue: None,
+//!     title: Some("Tiny".to_string()),
+//!     by: None,
+//!     copyright: None,
+//!     license: None,
+//!     colors: None,
+//! };
+//! let page = HtmlRenderer::default().render(&file, None);
+//! assert!(page.starts_with("<!DOCTYPE html>"));
+//! assert!(page.contains("<title>Tiny</title>"));
+//! ```
+
+use crate::nonogram::{Nonogram, Tile};
+use crate::nonogram_file::{Color, ColorCode, Hint, NonogramFile};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+const STYLE: &str = "\
+body { font-family: sans-serif; margin: 2em; }
+section { margin-bottom: 3em; }
+h1 { margin-bottom: 0.2em; }
+.by { margin-top: 0; font-style: italic; }
+table.nonogram { border-collapse: collapse; }
+table.nonogram th { font-weight: normal; font-size: 0.8em; color: #333; }
+table.nonogram thead th { vertical-align: bottom; padding: 0 0 0.3em; line-height: 1.3; }
+table.nonogram tbody th { text-align: right; padding: 0 0.5em 0 0; white-space: nowrap; }
+table.nonogram td { width: 1.4em; height: 1.4em; padding: 0; border: 1px solid #999; }
+table.nonogram td.guide-left { border-left: 2px solid #000; }
+table.nonogram td.guide-top { border-top: 2px solid #000; }
+table.nonogram td.filled { background: #000; }
+footer { margin-top: 1em; font-size: 0.8em; color: #555; }
+@media print {
+  body { margin: 0; }
+  section { page-break-inside: avoid; }
+  section.solution { page-break-before: always; }
+  table.nonogram td { -webkit-print-color-adjust: exact; print-color-adjust: exact; }
+}
+";
+
+/// Writes puzzles as standalone HTML pages.
+pub struct HtmlRenderer {
+    do_include_solution: bool,
+    guide_every: Option<usize>,
+}
+
+impl HtmlRenderer {
+    /// Add the solution after the puzzle, on a page of its own when printed.
+    pub fn with_solution(mut self, do_include_solution: bool) -> Self {
+        self.do_include_solution = do_include_solution;
+        self
+    }
+
+    /// Draw a thick line between every `every` rows and columns, or none with
+    /// 0.
+    pub fn with_guides(mut self, every: usize) -> Self {
+        self.guide_every = if every == 0 { None } else { Some(every) };
+        self
+    }
+
+    /// The page for `file`. The solution page, if it's included, shows
+    /// `solution`, or the file's goal if there is none. It's left out if
+    /// neither is given.
+    pub fn render(&self, file: &NonogramFile, solution: Option<&Nonogram>) -> String {
+        let goal = goal_nonogram(file);
+        let solution = solution.or(goal.as_ref());
+        let palette = palette(file);
+        let title = file.title.as_deref().unwrap_or("Nonogram");
+
+        let mut page = String::new();
+        page.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n");
+        page.push_str("<meta charset=\"utf-8\">\n");
+        writeln!(page, "<title>{}</title>", escape(title)).unwrap();
+        writeln!(page, "<style>\n{}</style>", STYLE).unwrap();
+        page.push_str("</head>\n<body>\n");
+
+        page.push_str("<section class=\"puzzle\">\n");
+        write_header(&mut page, file, title, None);
+        self.write_table(&mut page, file, None, &palette);
+        write_footer(&mut page, file);
+        page.push_str("</section>\n");
+
+        if let (true, Some(solution)) = (self.do_include_solution, solution) {
+            page.push_str("<section class=\"solution\">\n");
+            write_header(&mut page, file, title, Some("Solution"));
+            self.write_table(&mut page, file, Some(solution), &palette);
+            page.push_str("</section>\n");
+        }
+
+        page.push_str("</body>\n</html>\n");
+        page
+    }
+
+    /// The grid with clue headers, with the tiles of `solution` filled in if
+    /// it's given.
+    fn write_table(
+        &self,
+        page: &mut String,
+        file: &NonogramFile,
+        solution: Option<&Nonogram>,
+        palette: &HashMap<char, Color>,
+    ) {
+        page.push_str("<table class=\"nonogram\">\n<thead>\n<tr><th></th>");
+        for hints in &file.columns {
+            write!(page, "<th>{}</th>", clues_html(hints, "<br>", palette)).unwrap();
+        }
+        page.push_str("</tr>\n</thead>\n<tbody>\n");
+        for (row, hints) in file.rows.iter().enumerate() {
+            write!(page, "<tr><th>{}</th>", clues_html(hints, " ", palette)).unwrap();
+            for column in 0..file.width as usize {
+                let mut classes = Vec::new();
+                if self.is_guide_at(column) {
+                    classes.push("guide-left");
+                }
+                if self.is_guide_at(row) {
+                    classes.push("guide-top");
+                }
+                let tile = solution.and_then(|solution| solution.get_tile(row, column));
+                let color = match tile {
+                    Some(Tile::FilledWithColor(code)) => palette.get(&code),
+                    _ => None,
+                };
+                if matches!(tile, Some(tile) if tile.is_filled()) {
+                    classes.push("filled");
+                }
+                page.push_str("<td");
+                if !classes.is_empty() {
+                    write!(page, " class=\"{}\"", classes.join(" ")).unwrap();
+                }
+                if let Some(&color) = color {
+                    write!(page, " style=\"background: {}\"", css_color(color)).unwrap();
+                }
+                page.push_str("></td>");
+            }
+            page.push_str("</tr>\n");
+        }
+        page.push_str("</tbody>\n</table>\n");
+    }
+
+    fn is_guide_at(&self, index: usize) -> bool {
+        match self.guide_every {
+            Some(every) => index > 0 && index % every == 0,
+            None => false,
+        }
+    }
+}
+
+impl Default for HtmlRenderer {
+    fn default() -> Self {
+        HtmlRenderer {
+            do_include_solution: false,
+            guide_every: Some(5),
+        }
+    }
+}
+
+fn write_header(page: &mut String, file: &NonogramFile, title: &str, suffix: Option<&str>) {
+    match suffix {
+        Some(suffix) => writeln!(page, "<h1>{}: {}</h1>", escape(title), suffix).unwrap(),
+        None => writeln!(page, "<h1>{}</h1>", escape(title)).unwrap(),
+    }
+    if let Some(by) = &file.by {
+        writeln!(page, "<p class=\"by\">by {}</p>", escape(by)).unwrap();
+    }
+}
+
+fn write_footer(page: &mut String, file: &NonogramFile) {
+    let notes: Vec<String> = [&file.copyright, &file.license]
+        .iter()
+        .filter_map(|note| note.as_deref())
+        .map(escape)
+        .collect();
+    if !notes.is_empty() {
+        writeln!(page, "<footer>{}</footer>", notes.join("<br>")).unwrap();
+    }
+}
+
+/// The file's goal as a [Nonogram], if it has one.
+fn goal_nonogram(file: &NonogramFile) -> Option<Nonogram> {
+    let goal = file.goal_tiles()?;
+    let mut non = file.to_nonogram();
+    for (row, tiles) in goal.iter().enumerate() {
+        for (column, &tile) in tiles.iter().enumerate() {
+            non.set_tile(row, column, tile);
+        }
+    }
+    Some(non)
+}
+
+fn palette(file: &NonogramFile) -> HashMap<char, Color> {
+    file.colors
+        .iter()
+        .flatten()
+        .map(|definition| {
+            let ColorCode(code) = definition.code;
+            (code, definition.color)
+        })
+        .collect()
+}
+
+/// The clues of a line joined by `separator`, or 0 if it has none.
+fn clues_html(hints: &[Hint], separator: &str, palette: &HashMap<char, Color>) -> String {
+    if hints.is_empty() {
+        return "0".to_string();
+    }
+    let clues: Vec<String> = hints.iter().map(|hint| hint_html(hint, palette)).collect();
+    clues.join(separator)
+}
+
+/// A clue, in its color if it has one.
+fn hint_html(hint: &Hint, palette: &HashMap<char, Color>) -> String {
+    match hint.color.and_then(|ColorCode(code)| palette.get(&code)) {
+        Some(&color) => format!(
+            "<span style=\"color: {}\">{}</span>",
+            css_color(color),
+            hint.length
+        ),
+        None => hint.length.to_string(),
+    }
+}
+
+fn css_color(color: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.red, color.green, color.blue)
+}
+
+/// Escape the characters with a meaning in HTML text and attributes.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,169 @@
+//! Raster images of a [Nonogram]'s tiles
+//!
+//! [ImageRenderer] draws each tile as a square of pixels, for thumbnails of
+//! solved pictures. Filled tiles are black, or their own color if it is
+//! given with [with_colors](ImageRenderer::with_colors), not filled tiles are
+//! white and unknown tiles are gray.
+//!
+//! Images are written as binary [PBM and PPM](http://netpbm.sourceforge.net/doc/)
+//! files, which need nothing else to write, and, with the `png` feature, as
+//! PNG files. PBM files are black and white, so every filled tile is black and
+//! unknown tiles are white.
+//!
+//! ```
+//! use nonogram::image::ImageRenderer;
+//! use nonogram::{Nonogram, Tile};
+//!
+//! let mut non = Nonogram::new(vec![vec![1]], vec![vec![1]]);
+//! non.set_tile(0, 0, Tile::Filled);
+//! let ppm = ImageRenderer::default().with_scale(2).to_ppm(&non);
+//! assert!(ppm.starts_with(b"P6\n2 2\n255\n"));
+//! ```
+
+use crate::nonogram::{MaybeTile, Nonogram, Tile};
+use crate::nonogram_file::{Color, ColorCode, ColorDefinition};
+use std::collections::HashMap;
+
+const BLACK: Color = Color {
+    red: 0,
+    green: 0,
+    blue: 0,
+};
+const WHITE: Color = Color {
+    red: 255,
+    green: 255,
+    blue: 255,
+};
+const GRAY: Color = Color {
+    red: 192,
+    green: 192,
+    blue: 192,
+};
+
+pub struct ImageRenderer {
+    scale: usize,
+    palette: HashMap<char, Color>,
+}
+
+impl ImageRenderer {
+    /// Draw each tile as a `scale` by `scale` square of pixels, at least 1.
+    pub fn with_scale(mut self, scale: usize) -> Self {
+        self.scale = scale.max(1);
+        self
+    }
+
+    /// Draw tiles filled with a color in the colors defined by `colors`, such
+    /// as those of a [NonogramFile](crate::nonogram_file::NonogramFile).
+    /// Tiles with a color that isn't defined are black.
+    pub fn with_colors(mut self, colors: &[ColorDefinition]) -> Self {
+        self.palette = colors
+            .iter()
+            .map(|definition| {
+                let ColorCode(code) = definition.code;
+                (code, definition.color)
+            })
+            .collect();
+        self
+    }
+
+    /// The width and height of the image of `non` in pixels.
+    pub fn size(&self, non: &Nonogram) -> (usize, usize) {
+        (non.num_cols() * self.scale, non.num_rows() * self.scale)
+    }
+
+    /// The color of each pixel of the image of `non`, row by row.
+    pub fn pixels(&self, non: &Nonogram) -> Vec<Color> {
+        let (width, height) = self.size(non);
+        let mut pixels = Vec::with_capacity(width * height);
+        for row in 0..non.num_rows() {
+            let colors: Vec<Color> = non
+                .get_row(row)
+                .map(|&maybe_tile| self.tile_color(maybe_tile))
+                .collect();
+            for _ in 0..self.scale {
+                for &color in &colors {
+                    pixels.extend((0..self.scale).map(|_| color));
+                }
+            }
+        }
+        pixels
+    }
+
+    /// A binary PBM image, with every filled tile black.
+    pub fn to_pbm(&self, non: &Nonogram) -> Vec<u8> {
+        let (width, height) = self.size(non);
+        let mut bytes = format!("P4\n{} {}\n", width, height).into_bytes();
+        for row in 0..non.num_rows() {
+            let filled: Vec<bool> = non
+                .get_row(row)
+                .flat_map(|maybe_tile| {
+                    let is_filled = matches!(maybe_tile, Some(tile) if tile.is_filled());
+                    (0..self.scale).map(move |_| is_filled)
+                })
+                .collect();
+            // Each row of pixels is packed into bytes, most significant bit
+            // first, with 1 for black
+            let packed: Vec<u8> = filled
+                .chunks(8)
+                .map(|bits| {
+                    bits.iter()
+                        .enumerate()
+                        .filter(|&(_, &is_filled)| is_filled)
+                        .fold(0, |byte, (index, _)| byte | (0x80 >> index))
+                })
+                .collect();
+            for _ in 0..self.scale {
+                bytes.extend_from_slice(&packed);
+            }
+        }
+        bytes
+    }
+
+    /// A binary PPM image, in full color.
+    pub fn to_ppm(&self, non: &Nonogram) -> Vec<u8> {
+        let (width, height) = self.size(non);
+        let mut bytes = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+        bytes.extend(rgb_bytes(&self.pixels(non)));
+        bytes
+    }
+
+    /// A PNG image, in full color.
+    #[cfg(feature = "png")]
+    pub fn to_png(&self, non: &Nonogram) -> Result<Vec<u8>, png::EncodingError> {
+        let (width, height) = self.size(non);
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut bytes, width as u32, height as u32);
+            encoder.set_color(png::ColorType::RGB);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header()?;
+            writer.write_image_data(&rgb_bytes(&self.pixels(non)))?;
+        }
+        Ok(bytes)
+    }
+
+    fn tile_color(&self, maybe_tile: MaybeTile) -> Color {
+        match maybe_tile {
+            Some(Tile::Filled) => BLACK,
+            Some(Tile::FilledWithColor(code)) => self.palette.get(&code).copied().unwrap_or(BLACK),
+            Some(Tile::NotFilled) => WHITE,
+            None => GRAY,
+        }
+    }
+}
+
+impl Default for ImageRenderer {
+    fn default() -> Self {
+        ImageRenderer {
+            scale: 1,
+            palette: HashMap::new(),
+        }
+    }
+}
+
+fn rgb_bytes(pixels: &[Color]) -> Vec<u8> {
+    pixels
+        .iter()
+        .flat_map(|color| vec![color.red, color.green, color.blue])
+        .collect()
+}
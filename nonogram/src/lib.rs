@@ -1,7 +1,32 @@
 pub mod nonogram;
-pub use crate::nonogram::{Clue, LineClues, MaybeTile, Nonogram, Tile};
+pub use crate::nonogram::{
+    Clue, ClueError, ColoredClue, Line, LineClues, LineColors, LineProblem, LineState, MaybeTile,
+    Nonogram, Tile,
+};
+
+pub mod annotation;
 
 pub mod formatter;
 pub use crate::formatter::Formatter;
 
+pub mod html;
+
+pub mod image;
+
 pub mod nonogram_file;
+
+pub mod solver;
+
+pub mod difficulty;
+
+pub mod steps;
+
+pub mod bits;
+
+pub mod sat;
+
+#[cfg(feature = "rayon")]
+pub mod parallel;
+
+pub mod generator;
+pub use crate::generator::Generator;
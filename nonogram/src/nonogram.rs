@@ -23,15 +23,48 @@
 //! A [Nonogram] is considered a correct solution if all of the [Filled] tiles
 //! form lengths that match both the row and column "clues". Any unknown
 //! ([None]) tiles are treated as [NotFilled].
+//!
+//! [Nonogram::new] accepts any clues. Use [Nonogram::try_new] to reject clues
+//! that can never be satisfied before trying to play or solve the puzzle.
+//!
+//! Puzzles with colored clues are created with [Nonogram::colored] (or
+//! [Nonogram::try_colored]) from [ColoredClue]s. Each clue's run must then be
+//! [FilledWithColor](Tile::FilledWithColor) its color, and two runs of
+//! different colors may touch. A [Filled](Tile::Filled) tile in a colored
+//! puzzle is filled with a color that is not known yet.
+//!
+//! With the `serde` feature enabled, a [Nonogram] serializes as an object
+//! with `row_clues` and `column_clues` arrays and, if any tile is known, a
+//! `tiles` array of rows where each tile is `null`, `"filled"`,
+//! `"not_filled"`, or `{"filled_with_color": "<char>"}`. A colored puzzle also
+//! has `row_colors` and `column_colors` arrays, giving each clue's color or
+//! `null`.
 
+use crate::solver;
 use array2d::Array2D;
 use itertools::Itertools;
+use std::fmt;
 
 pub type MaybeTile = Option<Tile>;
 pub type Clue = usize;
 pub type LineClues = Vec<Clue>;
+/// The color of each of a line's clues, or [None] for a clue without a color.
+pub type LineColors = Vec<Option<char>>;
+
+/// A clue that may be colored.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ColoredClue {
+    pub length: Clue,
+    /// The color code of the clue, or [None] for the default color.
+    pub color: Option<char>,
+}
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum Tile {
     Filled,
     FilledWithColor(char),
@@ -48,11 +81,125 @@ impl Tile {
     }
 }
 
+/// A single row or column of a [Nonogram], identified by its index.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Line {
+    Row(usize),
+    Column(usize),
+}
+
+impl Line {
+    /// The `(row, column)` position of the tile at `index` along this line.
+    pub fn position(&self, index: usize) -> (usize, usize) {
+        match *self {
+            Line::Row(row) => (row, index),
+            Line::Column(column) => (index, column),
+        }
+    }
+
+    /// The line that crosses this one at `index`.
+    pub fn crossing(&self, index: usize) -> Line {
+        match *self {
+            Line::Row(_) => Line::Column(index),
+            Line::Column(_) => Line::Row(index),
+        }
+    }
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Line::Row(index) => write!(f, "row {}", index),
+            Line::Column(index) => write!(f, "column {}", index),
+        }
+    }
+}
+
+/// How the known tiles of a line compare to its clues.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum LineState {
+    /// The filled tiles form exactly the runs in the clues, treating unknown
+    /// tiles as not filled.
+    Satisfied,
+    /// The line is not satisfied yet, but the unknown tiles can still be set
+    /// to satisfy it.
+    Possible,
+    /// No way of setting the unknown tiles satisfies the line.
+    Contradicted,
+}
+
+/// The reason a single line's clues can never be satisfied.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum LineProblem {
+    /// A clue of length zero is mixed in with other clues. A lone clue of zero
+    /// is allowed, and means the line is empty.
+    ZeroLengthClue,
+    /// The clues plus the gaps between them need more tiles than the line has.
+    TooLong { required: usize, available: usize },
+}
+
+impl fmt::Display for LineProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LineProblem::ZeroLengthClue => write!(f, "contains a clue of length 0"),
+            LineProblem::TooLong {
+                required,
+                available,
+            } => write!(
+                f,
+                "needs {} tiles but only {} are available",
+                required, available
+            ),
+        }
+    }
+}
+
+/// Every problem found with a set of clues by [Nonogram::try_new].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ClueError {
+    /// Each line that can never be satisfied, along with why.
+    pub infeasible_lines: Vec<(Line, LineProblem)>,
+    /// The total number of filled tiles required by the row clues and by the
+    /// column clues, respectively, if they differ.
+    pub filled_mismatch: Option<(usize, usize)>,
+    /// Each color whose row clues and column clues fill a different number
+    /// of tiles, along with both totals.
+    pub color_mismatches: Vec<(char, usize, usize)>,
+}
+
+impl fmt::Display for ClueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid clues")?;
+        for (line, problem) in &self.infeasible_lines {
+            write!(f, "\n  {} {}", line, problem)?;
+        }
+        if let Some((row_total, column_total)) = self.filled_mismatch {
+            write!(
+                f,
+                "\n  row clues fill {} tiles but column clues fill {}",
+                row_total, column_total
+            )?;
+        }
+        for (color, row_total, column_total) in &self.color_mismatches {
+            write!(
+                f,
+                "\n  row clues fill {} tiles of color {} but column clues fill {}",
+                row_total, color, column_total
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ClueError {}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Nonogram {
     tiles: Array2D<MaybeTile>,
     row_clues: Vec<LineClues>,
     column_clues: Vec<LineClues>,
+    row_colors: Vec<LineColors>,
+    column_colors: Vec<LineColors>,
 }
 
 impl Nonogram {
@@ -60,13 +207,167 @@ impl Nonogram {
         let num_rows = row_clues.len();
         let num_cols = column_clues.len();
         let tiles = Array2D::filled_with(None, num_rows, num_cols);
+        let uncolored = |clues: &[LineClues]| -> Vec<LineColors> {
+            clues.iter().map(|line| vec![None; line.len()]).collect()
+        };
         Nonogram {
             tiles,
+            row_colors: uncolored(&row_clues),
+            column_colors: uncolored(&column_clues),
             row_clues,
             column_clues,
         }
     }
 
+    /// Create a new [Nonogram] whose clues may each have a color.
+    pub fn colored(row_clues: Vec<Vec<ColoredClue>>, column_clues: Vec<Vec<ColoredClue>>) -> Self {
+        let split = |clues: Vec<Vec<ColoredClue>>| -> (Vec<LineClues>, Vec<LineColors>) {
+            clues
+                .into_iter()
+                .map(|line| {
+                    line.into_iter()
+                        .map(|clue| (clue.length, clue.color))
+                        .unzip()
+                })
+                .unzip()
+        };
+        let (row_clues, row_colors) = split(row_clues);
+        let (column_clues, column_colors) = split(column_clues);
+        let mut non = Nonogram::new(row_clues, column_clues);
+        non.row_colors = row_colors;
+        non.column_colors = column_colors;
+        non
+    }
+
+    /// Create a new [Nonogram], first checking that every line's clues fit in
+    /// that line and that the rows and columns fill the same number of tiles.
+    pub fn try_new(
+        row_clues: Vec<LineClues>,
+        column_clues: Vec<LineClues>,
+    ) -> Result<Self, ClueError> {
+        let uncolored = |clues: Vec<LineClues>| -> Vec<Vec<ColoredClue>> {
+            clues
+                .into_iter()
+                .map(|line| {
+                    line.into_iter()
+                        .map(|length| ColoredClue {
+                            length,
+                            color: None,
+                        })
+                        .collect()
+                })
+                .collect()
+        };
+        Nonogram::try_colored(uncolored(row_clues), uncolored(column_clues))
+    }
+
+    /// Create a new [Nonogram] with colored clues, checking them like
+    /// [Nonogram::try_new]. Only clues of the same color need a gap between
+    /// them, and the rows and columns must fill the same number of tiles of
+    /// each color.
+    pub fn try_colored(
+        row_clues: Vec<Vec<ColoredClue>>,
+        column_clues: Vec<Vec<ColoredClue>>,
+    ) -> Result<Self, ClueError> {
+        let num_rows = row_clues.len();
+        let num_cols = column_clues.len();
+        let row_problems = row_clues.iter().enumerate().flat_map(|(i, clues)| {
+            Nonogram::line_problems(clues, num_cols)
+                .into_iter()
+                .map(move |problem| (Line::Row(i), problem))
+        });
+        let column_problems = column_clues.iter().enumerate().flat_map(|(i, clues)| {
+            Nonogram::line_problems(clues, num_rows)
+                .into_iter()
+                .map(move |problem| (Line::Column(i), problem))
+        });
+        let infeasible_lines: Vec<_> = row_problems.chain(column_problems).collect();
+
+        let total = |clues: &[Vec<ColoredClue>]| -> usize {
+            clues.iter().flatten().map(|clue| clue.length).sum()
+        };
+        let row_total = total(&row_clues);
+        let column_total = total(&column_clues);
+        let filled_mismatch = if row_total == column_total {
+            None
+        } else {
+            Some((row_total, column_total))
+        };
+        let color_total = |clues: &[Vec<ColoredClue>], color: char| -> usize {
+            clues
+                .iter()
+                .flatten()
+                .filter(|clue| clue.color == Some(color))
+                .map(|clue| clue.length)
+                .sum()
+        };
+        let mut colors: Vec<char> = row_clues
+            .iter()
+            .chain(column_clues.iter())
+            .flatten()
+            .filter_map(|clue| clue.color)
+            .collect();
+        colors.sort_unstable();
+        colors.dedup();
+        let color_mismatches: Vec<_> = colors
+            .into_iter()
+            .map(|color| {
+                let row_total = color_total(&row_clues, color);
+                let column_total = color_total(&column_clues, color);
+                (color, row_total, column_total)
+            })
+            .filter(|&(_, row_total, column_total)| row_total != column_total)
+            .collect();
+
+        if infeasible_lines.is_empty() && filled_mismatch.is_none() && color_mismatches.is_empty() {
+            Ok(Nonogram::colored(row_clues, column_clues))
+        } else {
+            Err(ClueError {
+                infeasible_lines,
+                filled_mismatch,
+                color_mismatches,
+            })
+        }
+    }
+
+    /// Create a [Nonogram] whose clues are derived from a complete grid of
+    /// tiles, with every tile already set to match that grid. Runs of
+    /// [FilledWithColor](Tile::FilledWithColor) tiles become clues of that
+    /// color.
+    pub fn from_solution(rows: &[Vec<Tile>]) -> Self {
+        let num_rows = rows.len();
+        let num_cols = rows.first().map(Vec::len).unwrap_or(0);
+        let mut tiles = Array2D::filled_with(None, num_rows, num_cols);
+        for (row_index, row) in rows.iter().enumerate() {
+            for (column_index, tile) in row.iter().enumerate() {
+                tiles[(row_index, column_index)] = Some(*tile);
+            }
+        }
+        let to_clues = |lines: Vec<Vec<(Clue, Tile)>>| -> (Vec<LineClues>, Vec<LineColors>) {
+            lines
+                .into_iter()
+                .map(|runs| {
+                    runs.into_iter()
+                        .map(|(length, tile)| match tile {
+                            Tile::FilledWithColor(color) => (length, Some(color)),
+                            _ => (length, None),
+                        })
+                        .unzip()
+                })
+                .unzip()
+        };
+        let (row_clues, row_colors) = to_clues(tiles.rows_iter().map(Nonogram::runs).collect());
+        let (column_clues, column_colors) =
+            to_clues(tiles.columns_iter().map(Nonogram::runs).collect());
+        Nonogram {
+            tiles,
+            row_clues,
+            column_clues,
+            row_colors,
+            column_colors,
+        }
+    }
+
     pub fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = &MaybeTile>> {
         self.tiles.rows_iter()
     }
@@ -107,6 +408,62 @@ impl Nonogram {
         &self.column_clues[index]
     }
 
+    /// Every row followed by every column.
+    pub fn lines(&self) -> impl Iterator<Item = Line> {
+        let rows = (0..self.num_rows()).map(Line::Row);
+        let columns = (0..self.num_cols()).map(Line::Column);
+        rows.chain(columns)
+    }
+
+    pub fn line_clues(&self, line: Line) -> &LineClues {
+        match line {
+            Line::Row(index) => self.row_clues_at(index),
+            Line::Column(index) => self.column_clues_at(index),
+        }
+    }
+
+    /// The color of each of the line's clues, in the same order as
+    /// [line_clues](Nonogram::line_clues). Every color is [None] unless the
+    /// puzzle was created with [Nonogram::colored].
+    pub fn line_colors(&self, line: Line) -> &[Option<char>] {
+        match line {
+            Line::Row(index) => &self.row_colors[index],
+            Line::Column(index) => &self.column_colors[index],
+        }
+    }
+
+    /// The line's clues along with their colors.
+    pub fn colored_clues(&self, line: Line) -> Vec<ColoredClue> {
+        self.line_clues(line)
+            .iter()
+            .zip(self.line_colors(line))
+            .map(|(&length, &color)| ColoredClue { length, color })
+            .collect()
+    }
+
+    /// Whether any clue has a color.
+    pub fn is_colored(&self) -> bool {
+        self.row_colors
+            .iter()
+            .chain(self.column_colors.iter())
+            .flatten()
+            .any(Option::is_some)
+    }
+
+    pub fn line_len(&self, line: Line) -> usize {
+        match line {
+            Line::Row(_) => self.num_cols(),
+            Line::Column(_) => self.num_rows(),
+        }
+    }
+
+    pub fn get_line(&self, line: Line) -> Vec<MaybeTile> {
+        match line {
+            Line::Row(index) => self.get_row(index).cloned().collect(),
+            Line::Column(index) => self.get_column(index).cloned().collect(),
+        }
+    }
+
     pub fn get_tile(&self, row: usize, column: usize) -> MaybeTile {
         self.tiles[(row, column)]
     }
@@ -119,32 +476,250 @@ impl Nonogram {
         self.tiles[(row, column)] = None;
     }
 
+    /// Whether every tile is known, regardless of whether they are correct.
+    pub fn is_complete(&self) -> bool {
+        self.rows().flatten().all(Option::is_some)
+    }
+
+    /// Check a single row or column against its clues, for feedback while the
+    /// puzzle is being played.
+    pub fn line_state(&self, line: Line) -> LineState {
+        let tiles = self.get_line(line);
+        let clues = self.line_clues(line);
+        let colors = self.line_colors(line);
+        if Nonogram::matches_clues(tiles.iter(), clues, colors) {
+            LineState::Satisfied
+        } else if solver::solve_colored_line(clues, colors, &tiles).is_some() {
+            LineState::Possible
+        } else {
+            LineState::Contradicted
+        }
+    }
+
+    /// For each of the line's clues, whether it is definitely placed: the
+    /// clue's run of tiles is filled and bounded by crossed out tiles or the
+    /// edge of the grid, and no other arrangement of the clues could put it
+    /// anywhere else. Unknown tiles are treated as possibly either value.
+    ///
+    /// A clue of zero counts as placed while no tile in the line is filled.
+    /// Nothing is placed in a line that is
+    /// [Contradicted](LineState::Contradicted).
+    pub fn placed_clues(&self, line: Line) -> Vec<bool> {
+        let tiles = self.get_line(line);
+        let clues = self.line_clues(line);
+        let mut placed = vec![false; clues.len()];
+        let analysis = match solver::LineAnalysis::new(clues, self.line_colors(line), &tiles) {
+            Some(analysis) => analysis,
+            None => return placed,
+        };
+        let is_filled = |index: usize| matches!(tiles[index], Some(tile) if tile.is_filled());
+        let is_crossed = |index: usize| tiles[index] == Some(Tile::NotFilled);
+        for block in &analysis.blocks {
+            if let [start] = block.starts[..] {
+                let end = start + block.length;
+                let is_bounded = (start == 0 || is_crossed(start - 1))
+                    && (end == tiles.len() || is_crossed(end));
+                placed[block.clue_index] = is_bounded && (start..end).all(is_filled);
+            }
+        }
+        if !(0..tiles.len()).any(is_filled) {
+            for (index, &clue) in clues.iter().enumerate() {
+                if clue == 0 {
+                    placed[index] = true;
+                }
+            }
+        }
+        placed
+    }
+
+    /// Every row and then every column whose [state](Nonogram::line_state) is
+    /// [Contradicted](LineState::Contradicted).
+    pub fn contradicted_lines(&self) -> impl Iterator<Item = Line> + '_ {
+        self.lines()
+            .filter(move |&line| self.line_state(line) == LineState::Contradicted)
+    }
+
     pub fn is_correct_solution(&self) -> bool {
-        self.row_clues == self.row_sequence_lengths()
-            && self.column_clues == self.column_sequence_lengths()
+        self.lines().all(|line| {
+            let tiles = self.get_line(line);
+            Nonogram::matches_clues(tiles.iter(), self.line_clues(line), self.line_colors(line))
+        })
     }
 
-    fn row_sequence_lengths(&self) -> Vec<LineClues> {
-        self.tiles
-            .rows_iter()
-            .map(|row_iter| Nonogram::sequence_lengths(row_iter))
-            .collect()
+    /// Whether the runs of filled tiles are exactly `clues`, treating unknown
+    /// tiles as not filled. A run matches a colored clue only if it is filled
+    /// with that color, while a clue without a color matches any run.
+    fn matches_clues<'a, I>(sequence: I, clues: &[Clue], colors: &[Option<char>]) -> bool
+    where
+        I: Iterator<Item = &'a MaybeTile>,
+    {
+        let runs = Nonogram::runs(sequence);
+        let clues: Vec<(Clue, Option<char>)> = clues
+            .iter()
+            .cloned()
+            .zip(colors.iter().cloned())
+            .filter(|&(clue, _)| clue > 0)
+            .collect();
+        runs.len() == clues.len()
+            && runs
+                .iter()
+                .zip(clues.iter())
+                .all(|(&(length, tile), &(clue, color))| {
+                    let is_right_color = match color {
+                        Some(color) => tile == Tile::FilledWithColor(color),
+                        None => true,
+                    };
+                    length == clue && is_right_color
+                })
     }
 
-    fn column_sequence_lengths(&self) -> Vec<LineClues> {
-        self.tiles
-            .columns_iter()
-            .map(|column_iter| Nonogram::sequence_lengths(column_iter))
-            .collect()
+    fn line_problems(clues: &[ColoredClue], available: usize) -> Vec<LineProblem> {
+        let mut problems = Vec::new();
+        if clues.len() > 1 && clues.iter().any(|clue| clue.length == 0) {
+            problems.push(LineProblem::ZeroLengthClue);
+        }
+        let required = Nonogram::min_line_length(clues);
+        if required > available {
+            problems.push(LineProblem::TooLong {
+                required,
+                available,
+            });
+        }
+        problems
+    }
+
+    /// The fewest tiles that can hold these clues, counting a single gap
+    /// between each pair of runs of the same color.
+    fn min_line_length(clues: &[ColoredClue]) -> usize {
+        let filled: usize = clues.iter().map(|clue| clue.length).sum();
+        let gaps = clues
+            .windows(2)
+            .filter(|pair| pair[0].color == pair[1].color)
+            .count();
+        filled + gaps
     }
 
-    fn sequence_lengths<'a, I>(sequence: I) -> LineClues
+    /// The length and tile of each run of filled tiles.
+    fn runs<'a, I>(sequence: I) -> Vec<(Clue, Tile)>
     where
         I: Iterator<Item = &'a MaybeTile>,
     {
         let sequence = sequence.map(|maybe_tile| maybe_tile.unwrap_or(Tile::NotFilled));
         let groups = sequence.group_by(|&t| t);
         let filled = groups.into_iter().filter(|(tile, _)| tile.is_filled());
-        filled.map(|(_, group)| group.count()).collect()
+        filled.map(|(tile, group)| (group.count(), tile)).collect()
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::{ColoredClue, LineClues, LineColors, MaybeTile, Nonogram};
+    use array2d::Array2D;
+    use serde::de::{self, Deserialize, Deserializer};
+    use serde::ser::{Serialize, Serializer};
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct NonogramData {
+        row_clues: Vec<LineClues>,
+        column_clues: Vec<LineClues>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        row_colors: Option<Vec<LineColors>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        column_colors: Option<Vec<LineColors>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tiles: Option<Vec<Vec<MaybeTile>>>,
+    }
+
+    impl Serialize for Nonogram {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let has_known_tiles = self.rows().flatten().any(Option::is_some);
+            let tiles = if has_known_tiles {
+                Some(self.rows().map(|row| row.cloned().collect()).collect())
+            } else {
+                None
+            };
+            let (row_colors, column_colors) = if self.is_colored() {
+                (
+                    Some(self.row_colors.clone()),
+                    Some(self.column_colors.clone()),
+                )
+            } else {
+                (None, None)
+            };
+            let data = NonogramData {
+                row_clues: self.row_clues.clone(),
+                column_clues: self.column_clues.clone(),
+                row_colors,
+                column_colors,
+                tiles,
+            };
+            data.serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Nonogram {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let data = NonogramData::deserialize(deserializer)?;
+            let row_clues = with_colors(data.row_clues, data.row_colors, "row")?;
+            let column_clues = with_colors(data.column_clues, data.column_colors, "column")?;
+            let mut non =
+                Nonogram::try_colored(row_clues, column_clues).map_err(de::Error::custom)?;
+            if let Some(tiles) = data.tiles {
+                let has_correct_dimensions = tiles.len() == non.num_rows()
+                    && tiles.iter().all(|row| row.len() == non.num_cols());
+                if !has_correct_dimensions {
+                    return Err(de::Error::custom(format!(
+                        "tiles must be {} rows of {} tiles each",
+                        non.num_rows(),
+                        non.num_cols()
+                    )));
+                }
+                if non.num_rows() > 0 && non.num_cols() > 0 {
+                    non.tiles = Array2D::from_rows(&tiles);
+                }
+            }
+            Ok(non)
+        }
+    }
+
+    /// Pair each clue with its color, if the puzzle has colors.
+    fn with_colors<E: de::Error>(
+        clues: Vec<LineClues>,
+        colors: Option<Vec<LineColors>>,
+        kind: &str,
+    ) -> Result<Vec<Vec<ColoredClue>>, E> {
+        let colors = match colors {
+            Some(colors) => colors,
+            None => clues.iter().map(|line| vec![None; line.len()]).collect(),
+        };
+        let has_one_color_per_clue = colors.len() == clues.len()
+            && colors
+                .iter()
+                .zip(clues.iter())
+                .all(|(colors, clues)| colors.len() == clues.len());
+        if !has_one_color_per_clue {
+            return Err(E::custom(format!(
+                "{}_colors must have a color for every {} clue",
+                kind, kind
+            )));
+        }
+        let lines = clues
+            .into_iter()
+            .zip(colors)
+            .map(|(clues, colors)| {
+                clues
+                    .into_iter()
+                    .zip(colors)
+                    .map(|(length, color)| ColoredClue { length, color })
+                    .collect()
+            })
+            .collect();
+        Ok(lines)
     }
 }
@@ -1,11 +1,20 @@
+use crate::nonogram::{ColoredClue, Nonogram, Tile};
+use crate::solver::{self, Status};
+use std::fmt;
+
 // Structs
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NonogramFile {
     pub width: u64,
     pub height: u64,
-    pub rows: Vec<Hint>,
-    pub columns: Vec<Hint>,
+    /// The hints for each row, from top to bottom.
+    pub rows: Vec<Vec<Hint>>,
+    /// The hints for each column, from left to right.
+    pub columns: Vec<Vec<Hint>>,
 
-    pub goal: Option<Cell>,
+    /// The solution, row by row.
+    pub goal: Option<Vec<Cell>>,
 
     pub catalogue: Option<String>,
     pub title: Option<String>,
@@ -15,23 +24,37 @@ pub struct NonogramFile {
     pub colors: Option<Vec<ColorDefinition>>,
 }
 
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum Cell {
     Empty,
     Filled(Option<ColorCode>),
 }
 
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Hint {
     pub length: u64,
     pub color: Option<ColorCode>,
 }
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ColorCode(pub char);
 
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ColorDefinition {
     pub code: ColorCode,
     pub color: Color,
 }
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Color {
     pub red: u8,
     pub green: u8,
@@ -42,8 +65,8 @@ pub struct Color {
 pub struct NonogramFileBuilder {
     pub width: Option<u64>,
     pub height: Option<u64>,
-    pub rows: Option<Vec<Hint>>,
-    pub columns: Option<Vec<Hint>>,
+    pub rows: Option<Vec<Vec<Hint>>>,
+    pub columns: Option<Vec<Vec<Hint>>>,
 
     pub goal: Option<Vec<Cell>>,
 
@@ -57,26 +80,24 @@ pub struct NonogramFileBuilder {
 
 macro_rules! add_value_fn {
     ( $key:ident<&str> ) => {
-        pub fn $key(&mut self, $key: &str) -> BuildResult {
+        pub fn $key(&mut self, $key: &str) -> BuildResult<'_> {
             match self.$key {
                 None => {
                     self.$key = Some($key.to_string());
                     Ok(self)
                 }
-                Some(_) => Err(Error::DuplicateValue(
-                    stringify!($key).to_string())),
+                Some(_) => Err(Error::DuplicateValue(stringify!($key).to_string())),
             }
         }
     };
     ( $key:ident<$type:ty> ) => {
-        pub fn $key(&mut self, $key: $type) -> BuildResult {
+        pub fn $key(&mut self, $key: $type) -> BuildResult<'_> {
             match self.$key {
                 None => {
                     self.$key = Some($key);
                     Ok(self)
                 }
-                Some(_) => Err(Error::DuplicateValue(
-                    stringify!($key).to_string())),
+                Some(_) => Err(Error::DuplicateValue(stringify!($key).to_string())),
             }
         }
     };
@@ -84,7 +105,7 @@ macro_rules! add_value_fn {
 
 macro_rules! add_value_fn_with_required {
     ( $key:ident<$type:ty> $(, $required:ident )* ) => {
-        pub fn $key(&mut self, $key: $type) -> BuildResult {
+        pub fn $key(&mut self, $key: $type) -> BuildResult<'_> {
             $(
                 if self.$required.is_none() {
                     return Err(Error::ThisNeededRequiredValue(
@@ -125,8 +146,8 @@ impl NonogramFileBuilder {
     add_value_fn!(width<u64>);
     add_value_fn!(height<u64>);
 
-    add_value_fn_with_required!(rows<Vec<Hint>>, width, height);
-    add_value_fn_with_required!(columns<Vec<Hint>>, width, height);
+    add_value_fn_with_required!(rows<Vec<Vec<Hint>>>, width, height);
+    add_value_fn_with_required!(columns<Vec<Vec<Hint>>>, width, height);
     add_value_fn_with_required!(goal<Vec<Cell>>, width, height);
 
     add_value_fn!(catalogue<&str>);
@@ -134,26 +155,180 @@ impl NonogramFileBuilder {
     add_value_fn!(by<&str>);
     add_value_fn!(copyright<&str>);
     add_value_fn!(license<&str>);
+    add_value_fn!(colors<Vec<ColorDefinition>>);
+
+    /// Check that every required value was given and that the rows, columns
+    /// and goal match the dimensions.
+    pub fn build(self) -> Result<NonogramFile> {
+        let width = self.width.ok_or(Error::MissingValue("width".to_string()))?;
+        let height = self
+            .height
+            .ok_or(Error::MissingValue("height".to_string()))?;
+        let rows = self.rows.ok_or(Error::MissingValue("rows".to_string()))?;
+        let columns = self
+            .columns
+            .ok_or(Error::MissingValue("columns".to_string()))?;
+        let file = NonogramFile {
+            width,
+            height,
+            rows,
+            columns,
+            goal: self.goal,
+            catalogue: self.catalogue,
+            title: self.title,
+            by: self.by,
+            copyright: self.copyright,
+            license: self.license,
+            colors: self.colors,
+        };
+        file.validate()?;
+        Ok(file)
+    }
 }
 
-pub enum Error {
-    DuplicateValue(String),
-    ThisNeededRequiredValue(String, String),
+fn check_length(key: &str, expected: u64, found: u64) -> Result<()> {
+    if expected == found {
+        Ok(())
+    } else {
+        Err(Error::WrongLength {
+            key: key.to_string(),
+            expected,
+            found,
+        })
+    }
 }
 
-pub type Result<T> = std::result::Result<T, Error>;
-pub type BuildResult<'a> = Result<&'a NonogramFileBuilder>;
+impl Default for NonogramFileBuilder {
+    fn default() -> Self {
+        NonogramFileBuilder::new()
+    }
+}
+
+impl NonogramFile {
+    /// Check that the rows, columns and goal match the dimensions, as
+    /// [NonogramFileBuilder::build] does. Use this on a file that was not
+    /// built, such as one that was deserialized.
+    pub fn validate(&self) -> Result<()> {
+        check_length("rows", self.height, self.rows.len() as u64)?;
+        check_length("columns", self.width, self.columns.len() as u64)?;
+        if let Some(goal) = &self.goal {
+            check_length(
+                "goal",
+                self.width.saturating_mul(self.height),
+                goal.len() as u64,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// A [Nonogram] with this file's clues, including their colors, and every
+    /// tile unknown.
+    pub fn to_nonogram(&self) -> Nonogram {
+        let to_clues = |hints: &[Vec<Hint>]| -> Vec<Vec<ColoredClue>> {
+            hints
+                .iter()
+                .map(|line| {
+                    line.iter()
+                        .map(|hint| ColoredClue {
+                            length: hint.length as usize,
+                            color: hint.color.map(|ColorCode(code)| code),
+                        })
+                        .collect()
+                })
+                .collect()
+        };
+        Nonogram::colored(to_clues(&self.rows), to_clues(&self.columns))
+    }
 
-// Main
-pub fn main() {
-    let input = "key this is the value";
-    println!("{:?}", first_word_and_rest(input));
+    /// The goal as rows of tiles, if the file has one.
+    pub fn goal_tiles(&self) -> Option<Vec<Vec<Tile>>> {
+        let goal = self.goal.as_ref()?;
+        let width = self.width as usize;
+        if width == 0 {
+            return Some(vec![Vec::new(); self.height as usize]);
+        }
+        let rows = goal
+            .chunks(width)
+            .map(|row| {
+                row.iter()
+                    .map(|cell| match cell {
+                        Cell::Empty => Tile::NotFilled,
+                        Cell::Filled(None) => Tile::Filled,
+                        Cell::Filled(Some(ColorCode(code))) => Tile::FilledWithColor(*code),
+                    })
+                    .collect()
+            })
+            .collect();
+        Some(rows)
+    }
+
+    /// Up to `max_solutions` solutions of the file's clues, in the colors of
+    /// its goal. Line logic and probing come first, and only if they stall is
+    /// there a search, so asking for two tells whether the solution is unique.
+    pub fn solve(&self, max_solutions: usize) -> Vec<Nonogram> {
+        let mut solution = self.to_nonogram();
+        let mut solutions = match solver::solve_with_probing(&mut solution) {
+            Ok((Status::Solved, _)) => vec![solution],
+            Ok((Status::Stalled, _)) => solver::search(&solution, max_solutions).solutions,
+            Err(_) => Vec::new(),
+        };
+        solutions.truncate(max_solutions);
+        for solution in &mut solutions {
+            self.color_solution(solution);
+        }
+        solutions
+    }
 
-    println!("finished main");
+    /// Copy the colors of the goal onto the tiles of `solution` that are
+    /// filled without a color, which the solver leaves when the clues don't
+    /// give the colors.
+    pub fn color_solution(&self, solution: &mut Nonogram) {
+        let goal = self.goal_tiles().unwrap_or_default();
+        for (row, tiles) in goal.iter().enumerate() {
+            for (column, &tile) in tiles.iter().enumerate() {
+                let is_colored = matches!(tile, Tile::FilledWithColor(_));
+                if is_colored && solution.get_tile(row, column) == Some(Tile::Filled) {
+                    solution.set_tile(row, column, tile);
+                }
+            }
+        }
+    }
 }
 
-fn first_word_and_rest(input: &str) -> (&str, &str) {
-    let index = input.find(' ').unwrap_or(input.len());
-    let (first, reset) = input.split_at(index);
-    (first, reset.trim_start())
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Error {
+    DuplicateValue(String),
+    ThisNeededRequiredValue(String, String),
+    MissingValue(String),
+    WrongLength {
+        key: String,
+        expected: u64,
+        found: u64,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::DuplicateValue(key) => write!(f, "\"{}\" is given more than once", key),
+            Error::ThisNeededRequiredValue(key, required) => {
+                write!(f, "\"{}\" must come after \"{}\"", key, required)
+            }
+            Error::MissingValue(key) => write!(f, "\"{}\" is missing", key),
+            Error::WrongLength {
+                key,
+                expected,
+                found,
+            } => write!(
+                f,
+                "\"{}\" has {} entries but should have {}",
+                key, found, expected
+            ),
+        }
+    }
 }
+
+impl std::error::Error for Error {}
+
+pub type Result<T> = std::result::Result<T, Error>;
+pub type BuildResult<'a> = Result<&'a NonogramFileBuilder>;
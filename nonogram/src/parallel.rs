@@ -0,0 +1,113 @@
+//! Solving with multiple threads, using [rayon]
+//!
+//! Only available with the `rayon` feature.
+//!
+//! [solve_all] spreads a whole collection of puzzles across threads. [solve]
+//! speeds up a single large puzzle instead: no row depends on any other row
+//! (and likewise for columns), so every row that needs checking is solved at
+//! once, then every column that changed as a result, and so on until nothing
+//! changes.
+
+use crate::bits::{self, BitGrid, LineBits};
+use crate::nonogram::{Line, LineClues, Nonogram};
+use crate::solver::{self, Contradiction, Status};
+use rayon::prelude::*;
+
+/// Run `solve` on every puzzle in parallel, returning each result in the same
+/// order as `puzzles`.
+///
+/// For example, `solve_all(&mut puzzles, solver::solve)`.
+pub fn solve_all<F, T>(puzzles: &mut [Nonogram], solve: F) -> Vec<T>
+where
+    F: Fn(&mut Nonogram) -> T + Sync + Send,
+    T: Send,
+{
+    puzzles.par_iter_mut().map(solve).collect()
+}
+
+/// Apply line logic like [solver::solve](crate::solver::solve), solving
+/// independent rows and columns in parallel.
+///
+/// [BitGrid] has no colors, so a puzzle with colored clues is solved with
+/// [solver::solve](crate::solver::solve) instead.
+pub fn solve(non: &mut Nonogram) -> Result<Status, Contradiction> {
+    if non.is_colored() {
+        return solver::solve(non);
+    }
+    let mut grid = BitGrid::from(&*non);
+    let result = grid.solve_parallel();
+    // Only copy newly known tiles so that colored tiles are kept.
+    for row in 0..non.num_rows() {
+        for column in 0..non.num_cols() {
+            if non.get_tile(row, column).is_some() {
+                continue;
+            }
+            if let Some(tile) = grid.get_tile(row, column) {
+                non.set_tile(row, column, tile);
+            }
+        }
+    }
+    result
+}
+
+impl BitGrid {
+    /// Apply line logic like [solve](BitGrid::solve), solving independent
+    /// rows and columns in parallel.
+    pub fn solve_parallel(&mut self) -> Result<Status, Contradiction> {
+        let mut dirty_rows = vec![true; self.num_rows()];
+        let mut dirty_columns = vec![true; self.num_cols()];
+        while dirty_rows.contains(&true) || dirty_columns.contains(&true) {
+            let solved_rows =
+                solve_dirty_lines(&self.row_clues, &self.rows, &dirty_rows, Line::Row)?;
+            dirty_rows.iter_mut().for_each(|dirty| *dirty = false);
+            self.apply(solved_rows, &mut dirty_columns);
+
+            let solved_columns = solve_dirty_lines(
+                &self.column_clues,
+                &self.columns,
+                &dirty_columns,
+                Line::Column,
+            )?;
+            dirty_columns.iter_mut().for_each(|dirty| *dirty = false);
+            self.apply(solved_columns, &mut dirty_rows);
+        }
+        if self.is_complete() {
+            Ok(Status::Solved)
+        } else {
+            Ok(Status::Stalled)
+        }
+    }
+
+    /// Set every newly known tile in `solved` and mark the lines crossing
+    /// them as dirty.
+    fn apply(&mut self, solved: Vec<(Line, LineBits)>, dirty_crossing: &mut [bool]) {
+        for (line, new) in solved {
+            let newly_known = new.known.and_not(&self.line(line).known);
+            let mut index = newly_known.next_set(0);
+            while index < newly_known.len() {
+                let (row, column) = line.position(index);
+                self.set_tile(row, column, new.get(index));
+                dirty_crossing[index] = true;
+                index = newly_known.next_set(index + 1);
+            }
+        }
+    }
+}
+
+fn solve_dirty_lines(
+    clues: &[LineClues],
+    lines: &[LineBits],
+    dirty: &[bool],
+    to_line: fn(usize) -> Line,
+) -> Result<Vec<(Line, LineBits)>, Contradiction> {
+    (0..lines.len())
+        .into_par_iter()
+        .filter(|&index| dirty[index])
+        .map(|index| {
+            let line = to_line(index);
+            bits::solve_line_bits(&clues[index], &lines[index])
+                .map(|solved| (line, solved))
+                .ok_or(Contradiction { line })
+        })
+        .collect()
+}
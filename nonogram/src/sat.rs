@@ -0,0 +1,373 @@
+//! Encoding a [Nonogram] as a boolean satisfiability (SAT) problem
+//!
+//! Puzzles that defeat line logic and probing can be handed to a SAT solver
+//! instead. An [Encoding] holds the puzzle as clauses in conjunctive normal
+//! form (CNF), which can be written out in the DIMACS format understood by
+//! nearly every SAT solver. With the `varisat` feature, it can also be solved
+//! directly with the [varisat](https://crates.io/crates/varisat) crate.
+//!
+//! Colored clues are supported, whether they come from a [Nonogram] created
+//! with [Nonogram::colored] or are passed to [Encoding::colored]. Two blocks
+//! of the same color need at least one tile between them, while blocks of
+//! different colors may touch.
+//!
+//! The encoding uses these variables:
+//!
+//! - For every tile and every color, whether the tile is filled with that
+//!   color. At most one of these can be true for a tile, and a tile with none
+//!   of them true is not filled.
+//! - For every clue and every position it could start at, whether it starts
+//!   there.
+//! - For every clue and every position it could start at, whether it starts at
+//!   or before there. These make it cheap to say that each clue starts exactly
+//!   once, and that each clue starts after the one before it has ended.
+//!
+//! A tile is filled with a color exactly when a clue of that color covers it.
+
+pub use crate::nonogram::ColoredClue;
+use crate::nonogram::{Line, Nonogram, Tile};
+use std::io::{self, Write};
+
+/// A literal in DIMACS form: a positive variable number for the variable
+/// being true, or its negation for the variable being false.
+pub type Literal = i64;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Encoding {
+    num_vars: usize,
+    clauses: Vec<Vec<Literal>>,
+    colors: Vec<Option<char>>,
+    /// cell_vars[row][column][i] is true when the tile is filled with
+    /// colors[i].
+    cell_vars: Vec<Vec<Vec<Literal>>>,
+}
+
+impl Encoding {
+    /// Encode a puzzle, including the colors of its clues and any tiles that
+    /// are already known.
+    pub fn new(non: &Nonogram) -> Self {
+        let row_clues: Vec<_> = (0..non.num_rows())
+            .map(|row| non.colored_clues(Line::Row(row)))
+            .collect();
+        let column_clues: Vec<_> = (0..non.num_cols())
+            .map(|column| non.colored_clues(Line::Column(column)))
+            .collect();
+        let mut encoding = Encoding::colored(&row_clues, &column_clues);
+        for row in 0..non.num_rows() {
+            for column in 0..non.num_cols() {
+                if let Some(tile) = non.get_tile(row, column) {
+                    encoding.fix_tile(row, column, tile);
+                }
+            }
+        }
+        encoding
+    }
+
+    /// Encode a puzzle whose clues may each have a color.
+    pub fn colored(row_clues: &[Vec<ColoredClue>], column_clues: &[Vec<ColoredClue>]) -> Self {
+        let mut colors: Vec<Option<char>> = row_clues
+            .iter()
+            .chain(column_clues.iter())
+            .flatten()
+            .map(|clue| clue.color)
+            .collect();
+        colors.sort();
+        colors.dedup();
+        if colors.is_empty() {
+            colors.push(None);
+        }
+
+        let mut encoding = Encoding {
+            num_vars: 0,
+            clauses: Vec::new(),
+            colors,
+            cell_vars: Vec::new(),
+        };
+        let num_rows = row_clues.len();
+        let num_cols = column_clues.len();
+        let num_colors = encoding.colors.len();
+        encoding.cell_vars = (0..num_rows)
+            .map(|_| {
+                (0..num_cols)
+                    .map(|_| (0..num_colors).map(|_| encoding.new_var()).collect())
+                    .collect()
+            })
+            .collect();
+        for row in 0..num_rows {
+            for column in 0..num_cols {
+                let vars = encoding.cell_vars[row][column].clone();
+                for (i, &a) in vars.iter().enumerate() {
+                    for &b in &vars[i + 1..] {
+                        encoding.clauses.push(vec![-a, -b]);
+                    }
+                }
+            }
+        }
+
+        for (row, clues) in row_clues.iter().enumerate() {
+            let cells = encoding.cell_vars[row].clone();
+            encoding.encode_line(&cells, clues);
+        }
+        for (column, clues) in column_clues.iter().enumerate() {
+            let cells: Vec<_> = encoding
+                .cell_vars
+                .iter()
+                .map(|row| row[column].clone())
+                .collect();
+            encoding.encode_line(&cells, clues);
+        }
+        encoding
+    }
+
+    pub fn num_vars(&self) -> usize {
+        self.num_vars
+    }
+
+    pub fn clauses(&self) -> &[Vec<Literal>] {
+        &self.clauses
+    }
+
+    /// Require the tile at `(row, column)` to be `tile`.
+    pub fn fix_tile(&mut self, row: usize, column: usize, tile: Tile) {
+        let vars = self.cell_vars[row][column].clone();
+        match tile {
+            Tile::NotFilled => self.clauses.extend(vars.iter().map(|&var| vec![-var])),
+            Tile::FilledWithColor(color) if self.colors.contains(&Some(color)) => {
+                let index = self.color_index(tile);
+                self.clauses.push(vec![vars[index]]);
+            }
+            _ => self.clauses.push(vars),
+        }
+    }
+
+    pub fn write_dimacs<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "p cnf {} {}", self.num_vars, self.clauses.len())?;
+        for clause in &self.clauses {
+            for literal in clause {
+                write!(writer, "{} ", literal)?;
+            }
+            writeln!(writer, "0")?;
+        }
+        Ok(())
+    }
+
+    pub fn to_dimacs(&self) -> String {
+        let mut bytes = Vec::new();
+        self.write_dimacs(&mut bytes)
+            .expect("writing to a Vec never fails");
+        String::from_utf8(bytes).expect("DIMACS output is always ASCII")
+    }
+
+    /// Turn a satisfying assignment into a grid of tiles. The model lists the
+    /// literals that are true, like the `v` lines of a solver's output; any
+    /// variable not listed is treated as false.
+    pub fn decode(&self, model: &[Literal]) -> Vec<Vec<Tile>> {
+        let mut is_true = vec![false; self.num_vars + 1];
+        for &literal in model {
+            if literal > 0 && (literal as usize) <= self.num_vars {
+                is_true[literal as usize] = true;
+            }
+        }
+        self.cell_vars
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|vars| {
+                        let color = vars.iter().position(|&var| is_true[var as usize]);
+                        match color.map(|index| self.colors[index]) {
+                            None => Tile::NotFilled,
+                            Some(None) => Tile::Filled,
+                            Some(Some(color)) => Tile::FilledWithColor(color),
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// A clause that rules out exactly this solution, so that solving again
+    /// finds a different one if there is one.
+    pub fn blocking_clause(&self, solution: &[Vec<Tile>]) -> Vec<Literal> {
+        let mut clause = Vec::new();
+        for (row, tiles) in solution.iter().enumerate() {
+            for (column, &tile) in tiles.iter().enumerate() {
+                let vars = &self.cell_vars[row][column];
+                if tile.is_filled() {
+                    clause.push(-vars[self.color_index(tile)]);
+                } else {
+                    clause.extend(vars.iter().cloned());
+                }
+            }
+        }
+        clause
+    }
+
+    fn new_var(&mut self) -> Literal {
+        self.num_vars += 1;
+        self.num_vars as Literal
+    }
+
+    fn color_index(&self, tile: Tile) -> usize {
+        let color = match tile {
+            Tile::FilledWithColor(color) => Some(color),
+            _ => None,
+        };
+        self.colors.iter().position(|&c| c == color).unwrap_or(0)
+    }
+
+    fn encode_line(&mut self, cells: &[Vec<Literal>], clues: &[ColoredClue]) {
+        let blocks: Vec<ColoredClue> = clues
+            .iter()
+            .cloned()
+            .filter(|clue| clue.length > 0)
+            .collect();
+        let len = cells.len();
+        // The number of tiles required between each block and the one before.
+        let gaps: Vec<usize> = (0..blocks.len())
+            .map(|j| (j > 0 && blocks[j].color == blocks[j - 1].color) as usize)
+            .collect();
+
+        let mut earliest = vec![0; blocks.len()];
+        for j in 1..blocks.len() {
+            earliest[j] = earliest[j - 1] + blocks[j - 1].length + gaps[j];
+        }
+        let mut latest = vec![0isize; blocks.len()];
+        for j in (0..blocks.len()).rev() {
+            let limit = if j + 1 == blocks.len() {
+                len as isize
+            } else {
+                latest[j + 1] - gaps[j + 1] as isize
+            };
+            latest[j] = limit - blocks[j].length as isize;
+        }
+        if (0..blocks.len()).any(|j| latest[j] < earliest[j] as isize) {
+            // The clues cannot fit in the line at all.
+            self.clauses.push(Vec::new());
+            return;
+        }
+        let latest: Vec<usize> = latest.into_iter().map(|l| l as usize).collect();
+
+        // starts[j][p - earliest[j]]: block j starts at p.
+        // by[j][p - earliest[j]]: block j starts at or before p.
+        let mut starts: Vec<Vec<Literal>> = Vec::with_capacity(blocks.len());
+        let mut by: Vec<Vec<Literal>> = Vec::with_capacity(blocks.len());
+        for j in 0..blocks.len() {
+            let range = earliest[j]..=latest[j];
+            let block_starts: Vec<_> = range.clone().map(|_| self.new_var()).collect();
+            let block_by: Vec<_> = range.map(|_| self.new_var()).collect();
+            for (k, (&start, &at_or_before)) in block_starts.iter().zip(block_by.iter()).enumerate()
+            {
+                self.clauses.push(vec![-start, at_or_before]);
+                if k == 0 {
+                    self.clauses.push(vec![-at_or_before, start]);
+                } else {
+                    let before = block_by[k - 1];
+                    self.clauses.push(vec![-before, at_or_before]);
+                    self.clauses.push(vec![-start, -before]);
+                    self.clauses.push(vec![-at_or_before, before, start]);
+                }
+            }
+            self.clauses.push(vec![*block_by.last().unwrap()]);
+
+            if j > 0 {
+                let previous_by = &by[j - 1];
+                for (k, &start) in block_starts.iter().enumerate() {
+                    // The previous block must have started early enough to end
+                    // (plus any gap) before this one starts.
+                    let p = earliest[j] + k;
+                    let distance = blocks[j - 1].length + gaps[j];
+                    if p < distance || p - distance < earliest[j - 1] {
+                        self.clauses.push(vec![-start]);
+                    } else if p - distance < latest[j - 1] {
+                        self.clauses
+                            .push(vec![-start, previous_by[p - distance - earliest[j - 1]]]);
+                    }
+                }
+            }
+            starts.push(block_starts);
+            by.push(block_by);
+        }
+
+        for (index, cell) in cells.iter().enumerate() {
+            for (color_index, &var) in cell.iter().enumerate() {
+                let color = self.colors[color_index];
+                let mut covering = Vec::new();
+                for (j, block) in blocks.iter().enumerate() {
+                    if block.color != color {
+                        continue;
+                    }
+                    for (k, &start) in starts[j].iter().enumerate() {
+                        let p = earliest[j] + k;
+                        if p <= index && index < p + block.length {
+                            covering.push(start);
+                        }
+                    }
+                }
+                for &start in &covering {
+                    self.clauses.push(vec![-start, var]);
+                }
+                let mut clause = vec![-var];
+                clause.extend(covering);
+                self.clauses.push(clause);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "varisat")]
+impl Encoding {
+    /// Find up to `max_solutions` solutions with the varisat SAT solver.
+    pub fn solutions(&self, max_solutions: usize) -> Vec<Vec<Vec<Tile>>> {
+        use varisat::{ExtendFormula, Lit, Solver};
+
+        let to_lits = |clause: &[Literal]| -> Vec<Lit> {
+            clause
+                .iter()
+                .map(|&literal| Lit::from_dimacs(literal as isize))
+                .collect()
+        };
+        let mut solver = Solver::new();
+        for clause in &self.clauses {
+            solver.add_clause(&to_lits(clause));
+        }
+        let mut solutions = Vec::new();
+        while solutions.len() < max_solutions {
+            match solver.solve() {
+                Ok(true) => {}
+                _ => break,
+            }
+            let model: Vec<Literal> = match solver.model() {
+                Some(model) => model.iter().map(|lit| lit.to_dimacs() as Literal).collect(),
+                None => break,
+            };
+            let solution = self.decode(&model);
+            solver.add_clause(&to_lits(&self.blocking_clause(&solution)));
+            solutions.push(solution);
+        }
+        solutions
+    }
+}
+
+/// Find up to `max_solutions` solutions of `non` with the varisat SAT solver.
+/// Tiles that are already known are kept as they are.
+///
+/// Searching for two solutions is enough to tell whether a puzzle's solution
+/// is unique.
+#[cfg(feature = "varisat")]
+pub fn solve(non: &Nonogram, max_solutions: usize) -> Vec<Nonogram> {
+    Encoding::new(non)
+        .solutions(max_solutions)
+        .into_iter()
+        .map(|grid| {
+            let mut solved = non.clone();
+            for (row, tiles) in grid.iter().enumerate() {
+                for (column, &tile) in tiles.iter().enumerate() {
+                    if solved.get_tile(row, column).is_none() {
+                        solved.set_tile(row, column, tile);
+                    }
+                }
+            }
+            solved
+        })
+        .collect()
+}
@@ -0,0 +1,604 @@
+//! Solving a [Nonogram] with line logic
+//!
+//! Line logic looks at a single row or column at a time. Every arrangement of
+//! the line's clues that agrees with its known tiles is considered, and any
+//! unknown tile that is the same in all of those arrangements is set. Whenever
+//! a tile is set, the line crossing it is checked again, until no line can make
+//! any more progress.
+//!
+//! Instead of listing every arrangement (there can be very many), each line is
+//! analyzed with two passes of dynamic programming: one finding which prefixes
+//! of the line can hold the first few clues, and one finding which suffixes can
+//! hold the last few. A clue can start at a position exactly when the prefix
+//! before it and the suffix after it both work out.
+//!
+//! Many puzzles that stall under line logic can still be solved without
+//! guessing by [solve_with_probing]. Probing tentatively sets an unknown tile
+//! and runs line logic over the whole puzzle. If that leads to a contradiction,
+//! the tile must be the opposite. If both choices work, any tile that ends up
+//! the same either way must be that.
+//!
+//! Anything else can be solved with [search], which guesses a tile whenever
+//! line logic stalls and backtracks when a guess leads to a contradiction.
+//!
+//! Puzzles with colored clues are solved the same way. A tile is only set to
+//! [FilledWithColor](Tile::FilledWithColor) once every arrangement agrees on
+//! its color, and a tile that was set to [Filled](Tile::Filled) (such as by a
+//! guess) is given its color as soon as that is known.
+
+use crate::nonogram::{Clue, Line, MaybeTile, Nonogram, Tile};
+use std::collections::VecDeque;
+use std::fmt;
+
+/// How far solving got without running into a contradiction.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Status {
+    /// Every tile is known.
+    Solved,
+    /// Some tiles are still unknown, but no line can make more progress.
+    Stalled,
+}
+
+/// A line whose known tiles cannot be reconciled with its clues.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Contradiction {
+    pub line: Line,
+}
+
+impl fmt::Display for Contradiction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} contradicts its clues", self.line)
+    }
+}
+
+impl std::error::Error for Contradiction {}
+
+/// How much work line logic needed to reach its result.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct Effort {
+    /// The number of rounds of checking lines, where each round checks the
+    /// lines changed by the round before it.
+    pub sweeps: usize,
+    /// The total number of times any line was checked.
+    pub lines_checked: usize,
+    /// The number of tiles probed by [solve_with_probing].
+    pub probes: usize,
+}
+
+impl Effort {
+    fn add(&mut self, other: Effort) {
+        self.sweeps += other.sweeps;
+        self.lines_checked += other.lines_checked;
+        self.probes += other.probes;
+    }
+}
+
+/// Apply line logic to every row and column until no more progress can be
+/// made.
+///
+/// Tiles are set on `non` as they are deduced, so when a [Contradiction] is
+/// returned the tiles may have been partially updated.
+pub fn solve(non: &mut Nonogram) -> Result<Status, Contradiction> {
+    solve_with_effort(non).map(|(status, _)| status)
+}
+
+/// The same as [solve], but also reports how much work it took.
+pub fn solve_with_effort(non: &mut Nonogram) -> Result<(Status, Effort), Contradiction> {
+    let mut effort = Effort::default();
+    let mut queue = LineQueue::new(non);
+    while let Some((line, sweep)) = queue.pop() {
+        effort.sweeps = effort.sweeps.max(sweep);
+        effort.lines_checked += 1;
+        for index in solve_line_in_place(non, line)? {
+            queue.push(line.crossing(index), sweep + 1);
+        }
+    }
+    let status = if non.is_complete() {
+        Status::Solved
+    } else {
+        Status::Stalled
+    };
+    Ok((status, effort))
+}
+
+/// Apply line logic until it stalls, then probe unknown tiles until one
+/// leads to progress, repeating until the puzzle is solved or neither can make
+/// any more progress.
+///
+/// The [Effort] includes the line logic done while probing.
+pub fn solve_with_probing(non: &mut Nonogram) -> Result<(Status, Effort), Contradiction> {
+    let mut effort = Effort::default();
+    loop {
+        let (status, line_effort) = solve_with_effort(non)?;
+        effort.add(line_effort);
+        if status == Status::Solved {
+            return Ok((status, effort));
+        }
+        match find_probe(non, &mut effort)? {
+            Some(Probe::Contradicted { branch, .. }) => *non = branch,
+            Some(Probe::Agreed { changes, .. }) => {
+                for ((row, column), tile) in changes {
+                    non.set_tile(row, column, tile);
+                }
+            }
+            None => return Ok((Status::Stalled, effort)),
+        }
+    }
+}
+
+/// The progress made by probing a single tile.
+pub(crate) enum Probe {
+    /// Setting the tile at `position` to the opposite of `tile` led to
+    /// `contradiction`, so it must be `tile`. `branch` is the puzzle after
+    /// setting it and applying line logic.
+    Contradicted {
+        position: (usize, usize),
+        tile: Tile,
+        contradiction: Contradiction,
+        branch: Nonogram,
+    },
+    /// Both choices for the tile at `position` worked, and every tile in
+    /// `changes` ended up the same either way.
+    Agreed {
+        position: (usize, usize),
+        changes: Vec<((usize, usize), Tile)>,
+    },
+}
+
+/// Probe unknown tiles of `non` (which should already be stalled under line
+/// logic) in order until one makes progress.
+pub(crate) fn find_probe(
+    non: &Nonogram,
+    effort: &mut Effort,
+) -> Result<Option<Probe>, Contradiction> {
+    for position in unknown_positions(non) {
+        effort.probes += 1;
+        let filled = probe_branch(non, position, Tile::Filled, effort);
+        let not_filled = probe_branch(non, position, Tile::NotFilled, effort);
+        let probe = match (filled, not_filled) {
+            (Err(_), Err(contradiction)) => return Err(contradiction),
+            (Err(contradiction), Ok(branch)) => Probe::Contradicted {
+                position,
+                tile: Tile::NotFilled,
+                contradiction,
+                branch,
+            },
+            (Ok(branch), Err(contradiction)) => Probe::Contradicted {
+                position,
+                // Line logic has given the tile its color if it has one
+                tile: branch
+                    .get_tile(position.0, position.1)
+                    .unwrap_or(Tile::Filled),
+                contradiction,
+                branch,
+            },
+            (Ok(filled), Ok(not_filled)) => {
+                let changes: Vec<_> = unknown_positions(non)
+                    .filter_map(|(row, column)| {
+                        let tile = filled.get_tile(row, column)?;
+                        if not_filled.get_tile(row, column) == Some(tile) {
+                            Some(((row, column), tile))
+                        } else {
+                            None
+                        }
+                    })
+                    .collect();
+                if changes.is_empty() {
+                    continue;
+                }
+                Probe::Agreed { position, changes }
+            }
+        };
+        return Ok(Some(probe));
+    }
+    Ok(None)
+}
+
+fn probe_branch(
+    non: &Nonogram,
+    (row, column): (usize, usize),
+    tile: Tile,
+    effort: &mut Effort,
+) -> Result<Nonogram, Contradiction> {
+    let mut branch = non.clone();
+    branch.set_tile(row, column, tile);
+    let (_, branch_effort) = solve_with_effort(&mut branch)?;
+    effort.add(branch_effort);
+    Ok(branch)
+}
+
+/// Every solution found by [search], along with how much guessing it took.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Search {
+    pub solutions: Vec<Nonogram>,
+    /// The total number of guesses made, including ones that were wrong.
+    pub guesses: usize,
+    /// The most guesses that were ever in effect at the same time.
+    pub max_depth: usize,
+}
+
+/// Find up to `max_solutions` solutions of `non`, guessing a tile and
+/// backtracking whenever line logic stalls.
+///
+/// Searching for two solutions is enough to tell whether a puzzle's solution
+/// is unique.
+pub fn search(non: &Nonogram, max_solutions: usize) -> Search {
+    let mut search = Search {
+        solutions: Vec::new(),
+        guesses: 0,
+        max_depth: 0,
+    };
+    search.explore(non.clone(), 0, max_solutions);
+    search
+}
+
+impl Search {
+    fn explore(&mut self, mut non: Nonogram, depth: usize, max_solutions: usize) {
+        if self.solutions.len() >= max_solutions {
+            return;
+        }
+        self.max_depth = self.max_depth.max(depth);
+        match solve(&mut non) {
+            Err(_) => {}
+            Ok(Status::Solved) => self.solutions.push(non),
+            Ok(Status::Stalled) => {
+                let (row, column) = match unknown_positions(&non).next() {
+                    Some(position) => position,
+                    None => return,
+                };
+                for &tile in &[Tile::Filled, Tile::NotFilled] {
+                    let mut guess = non.clone();
+                    guess.set_tile(row, column, tile);
+                    self.guesses += 1;
+                    self.explore(guess, depth + 1, max_solutions);
+                }
+            }
+        }
+    }
+}
+
+/// The position of every unknown tile, in row-major order.
+pub(crate) fn unknown_positions(non: &Nonogram) -> impl Iterator<Item = (usize, usize)> + '_ {
+    (0..non.num_rows())
+        .flat_map(move |row| (0..non.num_cols()).map(move |column| (row, column)))
+        .filter(move |&(row, column)| non.get_tile(row, column).is_none())
+}
+
+/// Deduce every tile of a single line that is the same in all arrangements of
+/// `clues` consistent with the known tiles of `line`.
+///
+/// Returns [None] if no arrangement is consistent with `line`.
+pub fn solve_line(clues: &[Clue], line: &[MaybeTile]) -> Option<Vec<MaybeTile>> {
+    solve_colored_line(clues, &[], line)
+}
+
+/// The same as [solve_line], but each clue has the color at the same index in
+/// `colors`. Clues past the end of `colors` have no color.
+pub fn solve_colored_line(
+    clues: &[Clue],
+    colors: &[Option<char>],
+    line: &[MaybeTile],
+) -> Option<Vec<MaybeTile>> {
+    let analysis = LineAnalysis::new(clues, colors, line)?;
+    let solved = line
+        .iter()
+        .enumerate()
+        .map(|(index, &maybe_tile)| analysis.deduced_tile(index, maybe_tile).or(maybe_tile))
+        .collect();
+    Some(solved)
+}
+
+/// Solve a single line of `non` and set any deduced tiles, returning the
+/// indices (along the line) of the tiles that changed.
+pub(crate) fn solve_line_in_place(
+    non: &mut Nonogram,
+    line: Line,
+) -> Result<Vec<usize>, Contradiction> {
+    let tiles = non.get_line(line);
+    let solved = solve_colored_line(non.line_clues(line), non.line_colors(line), &tiles)
+        .ok_or(Contradiction { line })?;
+    let mut changed = Vec::new();
+    for (index, (old, new)) in tiles.iter().zip(solved.iter()).enumerate() {
+        if let (Some(tile), true) = (new, old != new) {
+            let (row, column) = line.position(index);
+            non.set_tile(row, column, *tile);
+            changed.push(index);
+        }
+    }
+    Ok(changed)
+}
+
+/// Which tiles of a line can be filled or empty, and where each clue can be
+/// placed, given the line's known tiles.
+pub(crate) struct LineAnalysis {
+    /// One block for each non-zero clue, in order.
+    pub blocks: Vec<Block>,
+    can_be_filled: Vec<bool>,
+    can_be_empty: Vec<bool>,
+    /// The tile each index is filled with, if every block that can cover it
+    /// has the same color.
+    filled_as: Vec<MaybeTile>,
+}
+
+impl LineAnalysis {
+    /// Analyze a line, returning [None] if its known tiles contradict its
+    /// clues. Each clue has the color at the same index in `colors`, or no
+    /// color if `colors` is shorter.
+    pub fn new(clues: &[Clue], colors: &[Option<char>], line: &[MaybeTile]) -> Option<Self> {
+        // A clue of zero is sometimes used to mean an empty line.
+        let (clue_indices, lengths): (Vec<usize>, Vec<Clue>) = clues
+            .iter()
+            .cloned()
+            .enumerate()
+            .filter(|&(_, clue)| clue > 0)
+            .unzip();
+        let block_colors: Vec<Option<char>> = clue_indices
+            .iter()
+            .map(|&index| colors.get(index).cloned().flatten())
+            .collect();
+        let len = line.len();
+        let num_blocks = lengths.len();
+
+        let allows_empty = |index: usize| match line[index] {
+            Some(tile) => tile.is_not_filled(),
+            None => true,
+        };
+        // Each color used by a block, and which of them each block uses.
+        let mut distinct_colors = block_colors.clone();
+        distinct_colors.sort_unstable();
+        distinct_colors.dedup();
+        let color_indices: Vec<usize> = block_colors
+            .iter()
+            .map(|color| distinct_colors.iter().position(|c| c == color).unwrap())
+            .collect();
+        // For each color, the number of tiles starting at each index that
+        // could all be filled with it.
+        let fillable_runs: Vec<Vec<usize>> = distinct_colors
+            .iter()
+            .map(|&color| {
+                let mut fillable_run = vec![0; len + 1];
+                for index in (0..len).rev() {
+                    let allows_filled = match line[index] {
+                        Some(tile) => fits_color(tile, color),
+                        None => true,
+                    };
+                    if allows_filled {
+                        fillable_run[index] = fillable_run[index + 1] + 1;
+                    }
+                }
+                fillable_run
+            })
+            .collect();
+        // Whether block `block` can be placed at `start`, followed by either
+        // the end of the line or a gap, leaving the returned index for the
+        // remaining blocks. The gap is left out before a block of a different
+        // color, which may touch this one.
+        let place = |block: usize, start: usize| -> Option<usize> {
+            let end = start + lengths[block];
+            let needs_gap =
+                block + 1 == num_blocks || block_colors[block + 1] == block_colors[block];
+            if fillable_runs[color_indices[block]][start] < lengths[block] {
+                None
+            } else if end == len {
+                Some(len)
+            } else if !needs_gap {
+                Some(end)
+            } else if allows_empty(end) {
+                Some(end + 1)
+            } else {
+                None
+            }
+        };
+
+        // prefix[i][j]: the first i tiles can hold exactly the first j blocks.
+        let mut prefix = vec![vec![false; num_blocks + 1]; len + 1];
+        prefix[0][0] = true;
+        for index in 0..len {
+            for block in 0..=num_blocks {
+                if !prefix[index][block] {
+                    continue;
+                }
+                if allows_empty(index) {
+                    prefix[index + 1][block] = true;
+                }
+                if block < num_blocks {
+                    if let Some(next) = place(block, index) {
+                        prefix[next][block + 1] = true;
+                    }
+                }
+            }
+        }
+        if !prefix[len][num_blocks] {
+            return None;
+        }
+
+        // suffix[i][j]: the tiles from i onward can hold exactly the blocks
+        // from j onward.
+        let mut suffix = vec![vec![false; num_blocks + 1]; len + 1];
+        suffix[len][num_blocks] = true;
+        for index in (0..len).rev() {
+            for block in 0..=num_blocks {
+                let as_gap = allows_empty(index) && suffix[index + 1][block];
+                let as_block = block < num_blocks
+                    && matches!(place(block, index), Some(next) if suffix[next][block + 1]);
+                suffix[index][block] = as_gap || as_block;
+            }
+        }
+
+        let mut can_be_empty: Vec<bool> = (0..len)
+            .map(|index| {
+                allows_empty(index)
+                    && (0..=num_blocks)
+                        .any(|block| prefix[index][block] && suffix[index + 1][block])
+            })
+            .collect();
+        // For each color, incremented where a possible block starts and
+        // decremented where it ends, so a running sum counts the blocks of
+        // that color that can cover each tile.
+        let mut coverage = vec![vec![0isize; len + 1]; distinct_colors.len()];
+        let mut blocks = Vec::with_capacity(num_blocks);
+        for (block, &length) in lengths.iter().enumerate() {
+            let mut starts = Vec::new();
+            for start in 0..len {
+                if !prefix[start][block] {
+                    continue;
+                }
+                let next = match place(block, start) {
+                    Some(next) if suffix[next][block + 1] => next,
+                    _ => continue,
+                };
+                let end = start + length;
+                starts.push(start);
+                coverage[color_indices[block]][start] += 1;
+                coverage[color_indices[block]][end] -= 1;
+                // The gap right after a block is skipped over by the passes
+                // above, so it needs to be marked here.
+                if next > end {
+                    can_be_empty[end] = true;
+                }
+            }
+            blocks.push(Block {
+                clue_index: clue_indices[block],
+                length,
+                starts,
+            });
+        }
+
+        let mut can_be_filled = vec![false; len];
+        let mut filled_as = vec![None; len];
+        for (color, changes) in distinct_colors.iter().zip(coverage.iter()) {
+            let tile = match color {
+                Some(color) => Tile::FilledWithColor(*color),
+                None => Tile::Filled,
+            };
+            let mut covering = 0;
+            for (index, change) in changes.iter().take(len).enumerate() {
+                covering += change;
+                if covering > 0 {
+                    // Only one color may cover the tile for it to be known
+                    filled_as[index] = if can_be_filled[index] {
+                        None
+                    } else {
+                        Some(tile)
+                    };
+                    can_be_filled[index] = true;
+                }
+            }
+        }
+
+        Some(LineAnalysis {
+            blocks,
+            can_be_filled,
+            can_be_empty,
+            filled_as,
+        })
+    }
+
+    /// The tile at `index` if every arrangement agrees on it. A tile that
+    /// must be filled is only known once every arrangement agrees on its
+    /// color too.
+    pub fn forced_tile(&self, index: usize) -> MaybeTile {
+        match (self.can_be_filled[index], self.can_be_empty[index]) {
+            (true, false) => self.filled_as[index],
+            (false, true) => Some(Tile::NotFilled),
+            _ => None,
+        }
+    }
+
+    /// The [forced tile](LineAnalysis::forced_tile) at `index` if it tells
+    /// more than `known`: either the tile was unknown, or it was
+    /// [Filled](Tile::Filled) and its color is now known.
+    pub fn deduced_tile(&self, index: usize, known: MaybeTile) -> MaybeTile {
+        match (known, self.forced_tile(index)) {
+            (None, forced) => forced,
+            (Some(Tile::Filled), Some(tile @ Tile::FilledWithColor(_))) => Some(tile),
+            _ => None,
+        }
+    }
+}
+
+/// Whether a known tile can be part of a block of `color`. A block without a
+/// color can be any filled tile, and a [Filled](Tile::Filled) tile can be part
+/// of a block of any color.
+fn fits_color(tile: Tile, color: Option<char>) -> bool {
+    match (tile, color) {
+        (Tile::NotFilled, _) => false,
+        (Tile::FilledWithColor(tile_color), Some(color)) => tile_color == color,
+        _ => true,
+    }
+}
+
+/// Every position a single clue can be placed at within its line.
+pub(crate) struct Block {
+    /// The index of the clue in the line's clues.
+    pub clue_index: usize,
+    pub length: Clue,
+    /// Every index the block can start at, in increasing order. Never empty.
+    pub starts: Vec<usize>,
+}
+
+impl Block {
+    /// The tiles covered by this block no matter where it is placed.
+    pub fn overlap(&self) -> std::ops::Range<usize> {
+        let earliest = self.starts[0];
+        let latest = self.starts[self.starts.len() - 1];
+        latest..(earliest + self.length).max(latest)
+    }
+
+    /// Every tile the block can cover, wherever it is placed.
+    pub fn reach(&self) -> std::ops::Range<usize> {
+        self.starts[0]..self.starts[self.starts.len() - 1] + self.length
+    }
+
+    /// Whether the block could be placed anywhere within `range`.
+    pub fn can_start_within(&self, range: &std::ops::Range<usize>) -> bool {
+        self.starts.iter().any(|start| range.contains(start))
+    }
+}
+
+/// A queue of lines that still need to be checked, without duplicates, along
+/// with the sweep each line was queued for.
+pub(crate) struct LineQueue {
+    queue: VecDeque<(Line, usize)>,
+    queued: Vec<bool>,
+    num_rows: usize,
+}
+
+impl LineQueue {
+    pub fn new(non: &Nonogram) -> Self {
+        LineQueue::with_dimensions(non.num_rows(), non.num_cols())
+    }
+
+    /// A queue holding every row and then every column of a grid of this size.
+    pub fn with_dimensions(num_rows: usize, num_cols: usize) -> Self {
+        let rows = (0..num_rows).map(Line::Row);
+        let columns = (0..num_cols).map(Line::Column);
+        LineQueue {
+            queue: rows.chain(columns).map(|line| (line, 1)).collect(),
+            queued: vec![true; num_rows + num_cols],
+            num_rows,
+        }
+    }
+
+    pub fn push(&mut self, line: Line, sweep: usize) {
+        let index = self.index(line);
+        if !self.queued[index] {
+            self.queued[index] = true;
+            self.queue.push_back((line, sweep));
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<(Line, usize)> {
+        let (line, sweep) = self.queue.pop_front()?;
+        let index = self.index(line);
+        self.queued[index] = false;
+        Some((line, sweep))
+    }
+
+    fn index(&self, line: Line) -> usize {
+        match line {
+            Line::Row(row) => row,
+            Line::Column(column) => self.num_rows + column,
+        }
+    }
+}
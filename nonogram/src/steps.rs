@@ -0,0 +1,359 @@
+//! Explaining how a [Nonogram] is solved, one deduction at a time
+//!
+//! [steps] solves a puzzle with the same line logic as [solver](crate::solver),
+//! but instead of only setting tiles it reports each deduction as a [Step]: the
+//! line that was examined, the clue responsible, the [Technique] that
+//! justifies it, and the tiles that changed.
+//!
+//! Techniques are named after the usual ways people explain line logic:
+//!
+//! - [Overlap](Technique::Overlap): a clue covers the same tiles no matter
+//!   where it is placed, so those tiles must be filled.
+//! - [Edge](Technique::Edge): known tiles or the edge of the grid restrict
+//!   where clues can go, so a tile is forced one way or the other.
+//! - [GapTooSmall](Technique::GapTooSmall): a gap between crossed out tiles
+//!   is too small for any clue that could reach it, so it must be empty.
+//! - [Probe](Technique::Probe): only used [with probing](Steps::with_probing)
+//!   once line logic stalls. Trying one value for a tile led to a
+//!   contradiction, so the tile must be the other value, or both values led to
+//!   the same tiles being set. The step's line is the row or column through
+//!   the probed tile.
+//!
+//! In a puzzle with colored clues, a tile is only filled once its color is
+//! known. A step can also give its color to a tile that was already
+//! [Filled](Tile::Filled).
+
+use crate::formatter::Formatter;
+use crate::nonogram::{Line, MaybeTile, Nonogram, Tile};
+use crate::solver::{self, Contradiction, Effort, LineAnalysis, LineQueue, Probe, Status};
+use std::collections::VecDeque;
+use std::fmt;
+use std::ops::Range;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Technique {
+    Overlap,
+    Edge,
+    GapTooSmall,
+    Probe,
+}
+
+impl fmt::Display for Technique {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Technique::Overlap => "overlap",
+            Technique::Edge => "edge",
+            Technique::GapTooSmall => "gap too small",
+            Technique::Probe => "contradiction by probe",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// A single deduction made while solving.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Step {
+    pub line: Line,
+    /// The index of the clue in the line's clues that forced the change: the
+    /// clue that covers it for an overlap, and otherwise the clue whose reach
+    /// bounds it. [None] for probes and lines without clues.
+    pub clue: Option<usize>,
+    pub technique: Technique,
+    /// The `(row, column)` position of each tile that was set, along with what
+    /// it was set to.
+    pub changes: Vec<((usize, usize), Tile)>,
+    /// For a [Probe](Technique::Probe) step that ruled out a value, the line
+    /// that contradicted its clues when the tile had that value.
+    pub contradiction: Option<Line>,
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.line)?;
+        if let Some(clue) = self.clue {
+            write!(f, ", clue {}", clue)?;
+        }
+        write!(f, " ({}", self.technique)?;
+        if let Some(line) = self.contradiction {
+            write!(f, " in {}", line)?;
+        }
+        write!(f, "):")?;
+        for ((row, column), tile) in &self.changes {
+            let verb = if tile.is_filled() { "fill" } else { "cross" };
+            write!(f, " {} ({}, {})", verb, row, column)?;
+            if let Tile::FilledWithColor(color) = tile {
+                write!(f, " with {}", color)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An iterator over every deduction line logic makes on a puzzle.
+///
+/// Each step is applied to the puzzle as it is returned, so
+/// [nonogram](Steps::nonogram) always shows the state right after the last
+/// step.
+pub struct Steps {
+    non: Nonogram,
+    queue: LineQueue,
+    pending: VecDeque<Step>,
+    result: Option<Result<Status, Contradiction>>,
+    use_probing: bool,
+}
+
+/// Solve `non` with line logic, one [Step] at a time.
+pub fn steps(non: &Nonogram) -> Steps {
+    Steps {
+        non: non.clone(),
+        queue: LineQueue::new(non),
+        pending: VecDeque::new(),
+        result: None,
+        use_probing: false,
+    }
+}
+
+/// Describe every step of solving `non`, followed by the grid after that step
+/// as drawn by `formatter`.
+pub fn walkthrough(non: &Nonogram, formatter: &Formatter) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut steps = steps(non);
+    let mut step_number = 0;
+    while let Some(step) = steps.next() {
+        step_number += 1;
+        lines.push(format!("Step {}: {}", step_number, step));
+        lines.extend(formatter.get_lines(steps.nonogram()));
+        lines.push(String::new());
+    }
+    let summary = match steps.result() {
+        Some(Ok(Status::Solved)) => "Solved.".to_string(),
+        Some(Ok(Status::Stalled)) => "Stalled: line logic can make no more progress.".to_string(),
+        Some(Err(contradiction)) => format!("Stopped: {}.", contradiction),
+        None => unreachable!("steps are only finished once there is a result"),
+    };
+    lines.push(summary);
+    lines
+}
+
+impl Steps {
+    /// Probe unknown tiles whenever line logic stalls, instead of stopping.
+    pub fn with_probing(mut self) -> Self {
+        self.use_probing = true;
+        self
+    }
+
+    /// The puzzle with every step so far applied.
+    pub fn nonogram(&self) -> &Nonogram {
+        &self.non
+    }
+
+    /// How solving ended, or [None] if there may still be more steps.
+    pub fn result(&self) -> Option<Result<Status, Contradiction>> {
+        self.result
+    }
+
+    fn explain_line(&self, line: Line) -> Result<Vec<Step>, Contradiction> {
+        let tiles = self.non.get_line(line);
+        let clues = self.non.line_clues(line);
+        let analysis = LineAnalysis::new(clues, self.non.line_colors(line), &tiles)
+            .ok_or(Contradiction { line })?;
+        let mut steps: Vec<Step> = Vec::new();
+        for (index, &maybe_tile) in tiles.iter().enumerate() {
+            let tile = match analysis.deduced_tile(index, maybe_tile) {
+                Some(tile) => tile,
+                None => continue,
+            };
+            let (technique, clue) = Steps::classify(&analysis, &tiles, index, tile);
+            // Only a lone zero clue leaves a line with no blocks
+            let clue = clue.or_else(|| clues.iter().position(|&clue| clue == 0));
+            let change = (line.position(index), tile);
+            let existing = steps
+                .iter_mut()
+                .find(|step| step.technique == technique && step.clue == clue);
+            match existing {
+                Some(step) => step.changes.push(change),
+                None => steps.push(Step {
+                    line,
+                    clue,
+                    technique,
+                    changes: vec![change],
+                    contradiction: None,
+                }),
+            }
+        }
+        Ok(steps)
+    }
+
+    fn classify(
+        analysis: &LineAnalysis,
+        tiles: &[MaybeTile],
+        index: usize,
+        tile: Tile,
+    ) -> (Technique, Option<usize>) {
+        let blocks = &analysis.blocks;
+        if tile.is_filled() {
+            if let Some(block) = blocks.iter().find(|block| block.overlap().contains(&index)) {
+                return (Technique::Overlap, Some(block.clue_index));
+            }
+            // Every arrangement covers the tile, so some block can reach it
+            let reaching = blocks.iter().find(|block| block.reach().contains(&index));
+            return (Technique::Edge, reaching.map(|block| block.clue_index));
+        }
+        let gap = Steps::gap_around(tiles, index);
+        if blocks.iter().any(|block| block.can_start_within(&gap)) {
+            return (Technique::Edge, Steps::nearest_clue(analysis, index));
+        }
+        // A clue that reaches into the gap but can't fit in it
+        let too_long = blocks.iter().find(|block| {
+            let reach = block.reach();
+            reach.start < gap.end && gap.start < reach.end
+        });
+        let clue = match too_long {
+            Some(block) => Some(block.clue_index),
+            None => Steps::nearest_clue(analysis, index),
+        };
+        (Technique::GapTooSmall, clue)
+    }
+
+    /// The clue whose reach ends or starts closest to `index`, preferring the
+    /// earlier clue.
+    fn nearest_clue(analysis: &LineAnalysis, index: usize) -> Option<usize> {
+        let distance = |reach: Range<usize>| {
+            if index < reach.start {
+                reach.start - index
+            } else {
+                (index + 1).saturating_sub(reach.end)
+            }
+        };
+        analysis
+            .blocks
+            .iter()
+            .min_by_key(|block| distance(block.reach()))
+            .map(|block| block.clue_index)
+    }
+
+    fn explain_probe(&self) -> Result<Option<Step>, Contradiction> {
+        let probe = solver::find_probe(&self.non, &mut Effort::default())?;
+        let step = probe.map(|probe| match probe {
+            Probe::Contradicted {
+                position,
+                tile,
+                contradiction,
+                ..
+            } => Step {
+                line: Steps::line_toward(position, contradiction.line),
+                clue: None,
+                technique: Technique::Probe,
+                changes: vec![(position, tile)],
+                contradiction: Some(contradiction.line),
+            },
+            Probe::Agreed { position, changes } => Step {
+                line: Steps::probed_line(position, &changes),
+                clue: None,
+                technique: Technique::Probe,
+                changes,
+                contradiction: None,
+            },
+        });
+        Ok(step)
+    }
+
+    /// The row or column through the probed tile at `position` that reaches
+    /// the line that contradicted: that line itself if it goes through the
+    /// tile, otherwise the one crossing it.
+    fn line_toward((row, column): (usize, usize), contradiction: Line) -> Line {
+        match contradiction {
+            Line::Row(index) if index == row => contradiction,
+            Line::Column(index) if index == column => contradiction,
+            Line::Row(_) => Line::Column(column),
+            Line::Column(_) => Line::Row(row),
+        }
+    }
+
+    /// The row or column through the probed tile at `position` that holds
+    /// the most of `changes`, preferring the row.
+    fn probed_line((row, column): (usize, usize), changes: &[((usize, usize), Tile)]) -> Line {
+        let in_row = changes.iter().filter(|((r, _), _)| *r == row).count();
+        let in_column = changes.iter().filter(|((_, c), _)| *c == column).count();
+        if in_column > in_row {
+            Line::Column(column)
+        } else {
+            Line::Row(row)
+        }
+    }
+
+    /// The tiles around `index` that are not crossed out.
+    fn gap_around(tiles: &[MaybeTile], index: usize) -> Range<usize> {
+        let is_open = |tile: &MaybeTile| *tile != Some(Tile::NotFilled);
+        let start = tiles[..index]
+            .iter()
+            .rposition(|tile| !is_open(tile))
+            .map_or(0, |i| i + 1);
+        let end = tiles[index..]
+            .iter()
+            .position(|tile| !is_open(tile))
+            .map_or(tiles.len(), |i| index + i);
+        start..end
+    }
+}
+
+impl Iterator for Steps {
+    type Item = Step;
+
+    fn next(&mut self) -> Option<Step> {
+        loop {
+            if let Some(step) = self.pending.pop_front() {
+                for &((row, column), tile) in &step.changes {
+                    self.non.set_tile(row, column, tile);
+                }
+                return Some(step);
+            }
+            if self.result.is_some() {
+                return None;
+            }
+            let (line, _) = match self.queue.pop() {
+                Some(next) => next,
+                None if self.non.is_complete() => {
+                    self.result = Some(Ok(Status::Solved));
+                    return None;
+                }
+                None if self.use_probing => {
+                    match self.explain_probe() {
+                        Ok(Some(step)) => {
+                            for &((row, column), _) in &step.changes {
+                                self.queue.push(Line::Row(row), 0);
+                                self.queue.push(Line::Column(column), 0);
+                            }
+                            self.pending.push_back(step);
+                        }
+                        Ok(None) => self.result = Some(Ok(Status::Stalled)),
+                        Err(contradiction) => self.result = Some(Err(contradiction)),
+                    }
+                    continue;
+                }
+                None => {
+                    self.result = Some(Ok(Status::Stalled));
+                    return None;
+                }
+            };
+            match self.explain_line(line) {
+                Ok(steps) => {
+                    for step in &steps {
+                        for ((row, column), _) in &step.changes {
+                            let index = match line {
+                                Line::Row(_) => *column,
+                                Line::Column(_) => *row,
+                            };
+                            self.queue.push(line.crossing(index), 0);
+                        }
+                    }
+                    self.pending.extend(steps);
+                }
+                Err(contradiction) => {
+                    self.result = Some(Err(contradiction));
+                    return None;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use nonogram as non;
+use nonogram::annotation::{Annotations, Mark};
+
+/// Get the sample small nonogram
+///
+/// # # _ _
+/// # _ _ #
+/// # # # _
+fn get_small_nonogram() -> non::Nonogram {
+    let row_clues = vec![vec![2], vec![1, 1], vec![3]];
+    let column_clues = vec![vec![3], vec![1, 1], vec![1], vec![1]];
+    non::Nonogram::new(row_clues, column_clues)
+}
+
+#[test]
+fn marks_do_not_change_tiles() {
+    let non = get_small_nonogram();
+    let mut annotations = Annotations::for_nonogram(&non);
+    annotations.set(0, 0, Mark::Guess(non::Tile::Filled, 0));
+    annotations.set(0, 1, Mark::Maybe);
+    annotations.set(0, 2, Mark::Dot);
+    assert_eq!(non.get_tile(0, 0), None);
+    assert_eq!(annotations.get(0, 1), Some(Mark::Maybe));
+    assert_eq!(annotations.get(1, 1), None);
+}
+
+#[test]
+fn guesses_never_solve_the_puzzle() {
+    let non = get_small_nonogram();
+    let mut annotations = Annotations::for_nonogram(&non);
+    let solution = [(0, 0), (0, 1), (1, 0), (1, 3), (2, 0), (2, 1), (2, 2)];
+    for &(row, column) in &solution {
+        annotations.set(row, column, Mark::Guess(non::Tile::Filled, 0));
+    }
+    assert!(!non.is_correct_solution());
+}
+
+#[test]
+fn toggle_removes_the_same_mark() {
+    let mut annotations = Annotations::new(1, 1);
+    annotations.toggle(0, 0, Mark::Dot);
+    assert_eq!(annotations.get(0, 0), Some(Mark::Dot));
+    annotations.toggle(0, 0, Mark::Maybe);
+    assert_eq!(annotations.get(0, 0), Some(Mark::Maybe));
+    annotations.toggle(0, 0, Mark::Maybe);
+    assert_eq!(annotations.get(0, 0), None);
+}
+
+#[test]
+fn commit_sets_guessed_tiles() {
+    let mut non = get_small_nonogram();
+    let mut annotations = Annotations::for_nonogram(&non);
+    annotations.set(0, 0, Mark::Guess(non::Tile::Filled, 0));
+    annotations.set(0, 3, Mark::Guess(non::Tile::NotFilled, 1));
+    annotations.set(1, 1, Mark::Dot);
+
+    let committed = annotations.commit_guesses(&mut non);
+    assert_eq!(
+        committed,
+        vec![((0, 0), non::Tile::Filled), ((0, 3), non::Tile::NotFilled)]
+    );
+    assert_eq!(non.get_tile(0, 0), Some(non::Tile::Filled));
+    assert_eq!(non.get_tile(0, 3), Some(non::Tile::NotFilled));
+    assert!(annotations.guesses().is_empty());
+    assert_eq!(annotations.get(1, 1), Some(Mark::Dot));
+}
+
+#[test]
+fn discard_keeps_tiles_and_other_marks() {
+    let mut non = get_small_nonogram();
+    non.set_tile(2, 0, non::Tile::Filled);
+    let mut annotations = Annotations::for_nonogram(&non);
+    annotations.set(0, 0, Mark::Guess(non::Tile::Filled, 0));
+    annotations.set(1, 1, Mark::Maybe);
+
+    assert_eq!(annotations.discard_guesses(), 1);
+    assert_eq!(annotations.get(0, 0), None);
+    assert_eq!(annotations.get(1, 1), Some(Mark::Maybe));
+    assert_eq!(non.get_tile(0, 0), None);
+    assert_eq!(non.get_tile(2, 0), Some(non::Tile::Filled));
+}
+
+#[test]
+fn guesses_keep_their_pencil() {
+    let mut annotations = Annotations::new(1, 2);
+    annotations.set(0, 0, Mark::Guess(non::Tile::Filled, 0));
+    annotations.toggle(0, 1, Mark::Guess(non::Tile::Filled, 1));
+    assert_eq!(
+        annotations.get(0, 1),
+        Some(Mark::Guess(non::Tile::Filled, 1))
+    );
+    // The same guess in another pencil replaces it rather than removing it
+    annotations.toggle(0, 1, Mark::Guess(non::Tile::Filled, 2));
+    assert_eq!(
+        annotations.get(0, 1),
+        Some(Mark::Guess(non::Tile::Filled, 2))
+    );
+    assert_eq!(annotations.guesses().len(), 2);
+}
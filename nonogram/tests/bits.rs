@@ -0,0 +1,139 @@
+use nonogram as non;
+use nonogram::bits::{self, BitGrid, BitLine, LineBits};
+use nonogram::solver::{self, Status};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+const F: Option<non::Tile> = Some(non::Tile::Filled);
+const N: Option<non::Tile> = Some(non::Tile::NotFilled);
+const U: Option<non::Tile> = None;
+
+fn line_bits(tiles: &[non::MaybeTile]) -> LineBits {
+    let mut line = LineBits::new(tiles.len());
+    for (index, tile) in tiles.iter().enumerate() {
+        line.set(index, *tile);
+    }
+    line
+}
+
+fn tiles(line: &LineBits) -> Vec<non::MaybeTile> {
+    (0..line.len()).map(|index| line.get(index)).collect()
+}
+
+fn random_nonogram(rng: &mut StdRng, num_rows: usize, num_cols: usize) -> non::Nonogram {
+    let grid: Vec<Vec<_>> = (0..num_rows)
+        .map(|_| {
+            (0..num_cols)
+                .map(|_| {
+                    if rng.gen_bool(0.6) {
+                        non::Tile::Filled
+                    } else {
+                        non::Tile::NotFilled
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    let solution = non::Nonogram::from_solution(&grid);
+    non::Nonogram::new(
+        solution.row_clues().to_vec(),
+        solution.column_clues().to_vec(),
+    )
+}
+
+#[test]
+fn bit_line_finds_next_set_bit_across_words() {
+    let mut bits = BitLine::new(150);
+    bits.set(3, true);
+    bits.set(130, true);
+    assert_eq!(bits.next_set(0), 3);
+    assert_eq!(bits.next_set(4), 130);
+    assert_eq!(bits.next_set(131), 150);
+    assert_eq!(bits.count_ones(), 2);
+    bits.set(3, false);
+    assert!(!bits.get(3));
+}
+
+#[test]
+fn bit_line_solver_matches_line_solver() {
+    let cases: Vec<(Vec<usize>, Vec<non::MaybeTile>)> = vec![
+        (vec![4], vec![U, U, U, U, U]),
+        (vec![2, 2], vec![U, U, U, U, U]),
+        (vec![3], vec![F, U, U, U, U, U]),
+        (vec![2], vec![U, N, U, U, N, U]),
+        (vec![], vec![U, U, U]),
+        (vec![2], vec![F, N, F]),
+    ];
+    for (clues, line) in cases {
+        let expected = solver::solve_line(&clues, &line);
+        let actual = bits::solve_line_bits(&clues, &line_bits(&line)).map(|line| tiles(&line));
+        assert_eq!(actual, expected, "clues {:?}", clues);
+    }
+}
+
+#[test]
+fn bit_line_solver_matches_line_solver_across_words() {
+    let mut rng = StdRng::seed_from_u64(320);
+    for &len in &[1, 63, 64, 65, 130] {
+        for _ in 0..50 {
+            let solution: Vec<_> = (0..len)
+                .map(|_| {
+                    if rng.gen_bool(0.6) {
+                        non::Tile::Filled
+                    } else {
+                        non::Tile::NotFilled
+                    }
+                })
+                .collect();
+            let clues = non::Nonogram::from_solution(std::slice::from_ref(&solution)).row_clues()
+                [0]
+            .clone();
+            // Mostly tiles of the solution, with the odd wrong one.
+            let line: Vec<non::MaybeTile> = solution
+                .iter()
+                .map(|&tile| match rng.gen_range(0, 20) {
+                    0 => F,
+                    1..=5 => Some(tile),
+                    _ => U,
+                })
+                .collect();
+            let expected = solver::solve_line(&clues, &line);
+            let actual = bits::solve_line_bits(&clues, &line_bits(&line)).map(|line| tiles(&line));
+            assert_eq!(actual, expected, "clues {:?} line {:?}", clues, line);
+        }
+    }
+}
+
+#[test]
+fn converts_to_and_from_nonogram() {
+    let mut non = non::Nonogram::new(vec![vec![2], vec![1, 1], vec![3]], vec![vec![3]; 4]);
+    non.set_tile(0, 1, non::Tile::Filled);
+    non.set_tile(2, 3, non::Tile::NotFilled);
+    let grid = BitGrid::from(&non);
+    assert_eq!(grid.get_tile(0, 1), F);
+    assert_eq!(grid.line(non::Line::Column(3)).get(2), N);
+    assert_eq!(grid.to_nonogram(), non);
+}
+
+#[test]
+fn bit_grid_solves_like_nonogram() {
+    let mut rng = StdRng::seed_from_u64(32);
+    for &size in &[5, 20, 70, 100] {
+        let mut non = random_nonogram(&mut rng, size, size);
+        let mut grid = BitGrid::from(&non);
+        let expected = solver::solve(&mut non);
+        assert_eq!(grid.solve(), expected);
+        assert_eq!(grid.to_nonogram(), non);
+    }
+}
+
+#[test]
+fn bit_grid_solves_small_nonogram() {
+    let non = non::Nonogram::new(
+        vec![vec![2], vec![1, 1], vec![3]],
+        vec![vec![3], vec![1, 1], vec![1], vec![1]],
+    );
+    let mut grid = BitGrid::from(&non);
+    assert_eq!(grid.solve(), Ok(Status::Solved));
+    assert!(grid.to_nonogram().is_correct_solution());
+}
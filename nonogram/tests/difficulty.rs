@@ -0,0 +1,97 @@
+use nonogram as non;
+use nonogram::difficulty::{self, Category};
+use nonogram::Generator;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// Get the sample small nonogram
+///
+/// # # _ _
+/// # _ _ #
+/// # # # _
+fn get_small_nonogram() -> non::Nonogram {
+    let row_clues = vec![vec![2], vec![1, 1], vec![3]];
+    let column_clues = vec![vec![3], vec![1, 1], vec![1], vec![1]];
+    non::Nonogram::new(row_clues, column_clues)
+}
+
+#[test]
+fn small_nonogram_is_easy() {
+    let rating = difficulty::rate(&get_small_nonogram()).unwrap();
+    assert_eq!(rating.category, Category::Easy);
+    assert!(!rating.needs_backtracking);
+    assert!(rating.is_unique);
+    assert_eq!(rating.branching_depth, 0);
+    assert_eq!(rating.num_tiles, 12);
+    assert!(rating.sweeps > 0);
+}
+
+#[test]
+fn probing_nonogram_is_hard() {
+    let row_clues = vec![vec![1, 1], vec![1, 1], vec![1], vec![2]];
+    let column_clues = vec![vec![1], vec![1, 1], vec![1, 1], vec![2]];
+    let rating = difficulty::rate(&non::Nonogram::new(row_clues, column_clues)).unwrap();
+    assert_eq!(rating.category, Category::Hard);
+    assert!(rating.needs_probing);
+    assert!(rating.probes > 0);
+    assert!(!rating.needs_backtracking);
+    assert!(rating.is_unique);
+}
+
+#[test]
+fn ambiguous_nonogram_requires_guessing() {
+    let non = non::Nonogram::new(vec![vec![1], vec![1]], vec![vec![1], vec![1]]);
+    let rating = difficulty::rate(&non).unwrap();
+    assert_eq!(rating.category, Category::RequiresGuessing);
+    assert!(rating.needs_probing);
+    assert!(rating.needs_backtracking);
+    assert!(!rating.is_unique);
+    assert_eq!(rating.branching_depth, 1);
+}
+
+#[test]
+fn guessing_scores_higher() {
+    let easy = difficulty::rate(&get_small_nonogram()).unwrap();
+    let non = non::Nonogram::new(vec![vec![1], vec![1]], vec![vec![1], vec![1]]);
+    let guessing = difficulty::rate(&non).unwrap();
+    assert!(guessing.score > easy.score);
+}
+
+#[test]
+fn unsolvable_nonogram_is_not_rated() {
+    let non = non::Nonogram::new(vec![vec![2], vec![]], vec![vec![1], vec![2]]);
+    assert_eq!(difficulty::rate(&non), None);
+}
+
+#[test]
+fn generator_respects_max_difficulty() {
+    let mut rng = StdRng::seed_from_u64(29);
+    let generated = Generator::new(6, 6)
+        .max_difficulty(Category::Easy)
+        .generate(&mut rng)
+        .unwrap();
+    let rating = difficulty::rate(&generated.puzzle).unwrap();
+    assert_eq!(rating.category, Category::Easy);
+}
+
+#[test]
+fn generator_can_allow_probing() {
+    let mut rng = StdRng::seed_from_u64(31);
+    let generated = Generator::new(8, 8)
+        .max_difficulty(Category::Hard)
+        .generate(&mut rng)
+        .unwrap();
+    let rating = difficulty::rate(&generated.puzzle).unwrap();
+    assert!(rating.category <= Category::Hard);
+    assert!(!rating.needs_backtracking);
+}
+
+#[test]
+fn generator_can_allow_guessing() {
+    let mut rng = StdRng::seed_from_u64(29);
+    let generated = Generator::new(10, 10)
+        .max_difficulty(Category::RequiresGuessing)
+        .generate(&mut rng)
+        .unwrap();
+    assert!(difficulty::rate(&generated.puzzle).unwrap().is_unique);
+}
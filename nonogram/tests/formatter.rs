@@ -0,0 +1,131 @@
+use nonogram as non;
+use nonogram::formatter::ColorMode;
+use nonogram::nonogram_file::{Color, ColorCode, ColorDefinition};
+use nonogram::Formatter;
+
+/// A 6x6 nonogram with only the first row filled in.
+fn get_nonogram() -> non::Nonogram {
+    let row_clues = vec![vec![6], vec![1], vec![1], vec![1], vec![1], vec![1]];
+    let column_clues = vec![vec![6], vec![1], vec![1], vec![1], vec![1], vec![1]];
+    let mut non = non::Nonogram::new(row_clues, column_clues);
+    for column in 0..6 {
+        non.set_tile(0, column, non::Tile::Filled);
+    }
+    non
+}
+
+#[test]
+fn no_guides_by_default() {
+    let formatter = Formatter::new("#", "x", "_", false);
+    let lines = formatter.get_lines(&get_nonogram());
+    assert_eq!(lines.len(), 6);
+    assert_eq!(lines[0], "# # # # # #");
+    assert_eq!(lines[1], "_ _ _ _ _ _");
+}
+
+#[test]
+fn guides_split_rows_and_columns() {
+    let formatter = Formatter::new("#", "x", "_", false).with_guides(3);
+    let lines = formatter.get_lines(&get_nonogram());
+    assert_eq!(
+        lines,
+        vec![
+            "# # #|# # #",
+            "_ _ _|_ _ _",
+            "_ _ _|_ _ _",
+            "-----+-----",
+            "_ _ _|_ _ _",
+            "_ _ _|_ _ _",
+            "_ _ _|_ _ _",
+        ]
+    );
+}
+
+#[test]
+fn guides_line_up_with_clues() {
+    let formatter = Formatter::default().with_guides(3);
+    let lines = formatter.get_lines(&get_nonogram());
+    assert_eq!(
+        lines,
+        vec![
+            "   6 1 1 1 1 1",
+            "   ___________",
+            "6 |# # #|# # #",
+            "1 |_ _ _|_ _ _",
+            "1 |_ _ _|_ _ _",
+            "  |-----+-----",
+            "1 |_ _ _|_ _ _",
+            "1 |_ _ _|_ _ _",
+            "1 |_ _ _|_ _ _",
+        ]
+    );
+}
+
+#[test]
+fn rulers_number_lines() {
+    let formatter = Formatter::new("#", "x", "_", false)
+        .with_guides(2)
+        .with_rulers(true);
+    let lines = formatter.get_lines(&get_nonogram());
+    assert_eq!(lines[0], "# #|# #|# # 1");
+    assert_eq!(lines[1], "_ _|_ _|_ _ 2");
+    assert_eq!(lines[3], "_ _|_ _|_ _");
+    assert_eq!(lines[4], "_ _|_ _|_ _ 4");
+    assert_eq!(lines.last().unwrap(), "1 2   4   6");
+}
+
+/// A 1x3 nonogram filled with a defined color, an undefined color and no
+/// color.
+fn get_colored_nonogram() -> non::Nonogram {
+    let mut non = non::Nonogram::new(vec![vec![3]], vec![vec![1], vec![1], vec![1]]);
+    non.set_tile(0, 0, non::Tile::FilledWithColor('a'));
+    non.set_tile(0, 1, non::Tile::FilledWithColor('b'));
+    non.set_tile(0, 2, non::Tile::Filled);
+    non
+}
+
+fn get_colors() -> Vec<ColorDefinition> {
+    vec![ColorDefinition {
+        code: ColorCode('a'),
+        color: Color {
+            red: 255,
+            green: 0,
+            blue: 0,
+        },
+    }]
+}
+
+#[test]
+fn colors_are_off_by_default() {
+    let formatter = Formatter::new("#", "x", "_", false);
+    assert_eq!(formatter.get_lines(&get_colored_nonogram()), vec!["# # #"]);
+}
+
+#[test]
+fn true_color_tiles() {
+    let formatter =
+        Formatter::new("#", "x", "_", false).with_colors(ColorMode::TrueColor, &get_colors());
+    assert_eq!(
+        formatter.get_lines(&get_colored_nonogram()),
+        vec!["\x1b[38;2;255;0;0m#\x1b[0m # #"]
+    );
+}
+
+#[test]
+fn ansi256_tiles() {
+    let formatter =
+        Formatter::new("#", "x", "_", false).with_colors(ColorMode::Ansi256, &get_colors());
+    assert_eq!(
+        formatter.get_lines(&get_colored_nonogram()),
+        vec!["\x1b[38;5;196m#\x1b[0m # #"]
+    );
+}
+
+#[test]
+fn ansi256_picks_the_nearest_color() {
+    let code = |red, green, blue| ColorMode::Ansi256.escape_code(Color { red, green, blue });
+    assert_eq!(code(0, 0, 0), "\x1b[38;5;16m");
+    assert_eq!(code(255, 255, 255), "\x1b[38;5;231m");
+    assert_eq!(code(0, 135, 255), "\x1b[38;5;33m");
+    assert_eq!(code(128, 128, 128), "\x1b[38;5;244m");
+}
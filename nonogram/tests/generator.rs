@@ -0,0 +1,50 @@
+use nonogram::difficulty::{self, Category};
+use nonogram::solver::{self, Status};
+use nonogram::Generator;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[test]
+fn generated_puzzle_is_solvable_with_line_logic() {
+    let mut rng = StdRng::seed_from_u64(28);
+    for _ in 0..10 {
+        let generated = Generator::new(8, 10).generate(&mut rng).unwrap();
+        assert_eq!(generated.puzzle.num_rows(), 8);
+        assert_eq!(generated.puzzle.num_cols(), 10);
+        assert!(generated.solution.is_correct_solution());
+
+        let mut puzzle = generated.puzzle.clone();
+        assert_eq!(solver::solve(&mut puzzle), Ok(Status::Solved));
+        assert_eq!(puzzle, generated.solution);
+    }
+}
+
+#[test]
+fn generated_puzzle_starts_unknown() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let generated = Generator::new(5, 5).density(0.7).generate(&mut rng).unwrap();
+    assert!(generated.puzzle.rows().flatten().all(Option::is_none));
+}
+
+#[test]
+fn generated_puzzle_is_no_harder_than_the_cap() {
+    let mut rng = StdRng::seed_from_u64(1);
+    for &max_category in &[Category::Easy, Category::Medium, Category::Hard] {
+        let generator = Generator::new(6, 6).max_difficulty(max_category);
+        for _ in 0..5 {
+            let generated = generator.generate(&mut rng).unwrap();
+            let rating = difficulty::rate(&generated.puzzle).unwrap();
+            assert!(rating.category <= max_category);
+            assert!(rating.is_unique);
+        }
+    }
+}
+
+#[test]
+fn non_finite_density_keeps_the_default() {
+    let mut rng = StdRng::seed_from_u64(2);
+    for &density in &[f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        let generated = Generator::new(4, 4).density(density).generate(&mut rng);
+        assert!(generated.is_some());
+    }
+}
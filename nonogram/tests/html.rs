@@ -0,0 +1,110 @@
+use nonogram as non;
+use nonogram::html::HtmlRenderer;
+use nonogram::nonogram_file::{Cell, Color, ColorCode, ColorDefinition, Hint, NonogramFile};
+
+fn hint(length: u64) -> Hint {
+    Hint {
+        length,
+        color: None,
+    }
+}
+
+/// A 2x2 puzzle with a filled diagonal.
+fn get_file() -> NonogramFile {
+    NonogramFile {
+        width: 2,
+        height: 2,
+        rows: vec![vec![hint(1)], vec![hint(1)]],
+        columns: vec![vec![hint(1)], vec![hint(1)]],
+        goal: Some(vec![
+            Cell::Filled(None),
+            Cell::Empty,
+            Cell::Empty,
+            Cell::Filled(None),
+        ]),
+        catalogue: None,
+        title: Some("Fish & <Chips>".to_string()),
+        by: Some("Someone".to_string()),
+        copyright: Some("(c) 2020".to_string()),
+        license: Some("CC-BY".to_string()),
+        colors: None,
+    }
+}
+
+#[test]
+fn metadata_is_escaped() {
+    let page = HtmlRenderer::default().render(&get_file(), None);
+    assert!(page.contains("<title>Fish &amp; &lt;Chips&gt;</title>"));
+    assert!(page.contains("<h1>Fish &amp; &lt;Chips&gt;</h1>"));
+    assert!(page.contains("<p class=\"by\">by Someone</p>"));
+    assert!(page.contains("<footer>(c) 2020<br>CC-BY</footer>"));
+}
+
+#[test]
+fn puzzle_has_a_cell_per_tile_and_no_solution() {
+    let page = HtmlRenderer::default().render(&get_file(), None);
+    assert_eq!(page.matches("<td").count(), 4);
+    assert!(!page.contains("class=\"filled\""));
+    assert!(!page.contains("class=\"solution\""));
+}
+
+#[test]
+fn solution_comes_from_the_goal() {
+    let page = HtmlRenderer::default()
+        .with_solution(true)
+        .render(&get_file(), None);
+    assert!(page.contains("<section class=\"solution\">"));
+    assert_eq!(page.matches("<td class=\"filled\">").count(), 2);
+}
+
+#[test]
+fn solution_is_left_out_without_a_goal() {
+    let mut file = get_file();
+    file.goal = None;
+    let page = HtmlRenderer::default()
+        .with_solution(true)
+        .render(&file, None);
+    assert!(!page.contains("class=\"solution\""));
+
+    let mut solution = file.to_nonogram();
+    solution.set_tile(0, 1, non::Tile::Filled);
+    solution.set_tile(1, 0, non::Tile::Filled);
+    let page = HtmlRenderer::default()
+        .with_solution(true)
+        .render(&file, Some(&solution));
+    assert!(page.contains("class=\"solution\""));
+    assert_eq!(page.matches("class=\"filled\"").count(), 2);
+}
+
+#[test]
+fn colors_are_drawn() {
+    let mut file = get_file();
+    let red = Hint {
+        length: 1,
+        color: Some(ColorCode('r')),
+    };
+    file.rows[0] = vec![red.clone()];
+    file.columns[0] = vec![red];
+    file.goal.as_mut().unwrap()[0] = Cell::Filled(Some(ColorCode('r')));
+    file.colors = Some(vec![ColorDefinition {
+        code: ColorCode('r'),
+        color: Color {
+            red: 255,
+            green: 0,
+            blue: 0,
+        },
+    }]);
+    let page = HtmlRenderer::default()
+        .with_solution(true)
+        .render(&file, None);
+    assert!(page.contains("<span style=\"color: #ff0000\">1</span>"));
+    assert!(page.contains("<td class=\"filled\" style=\"background: #ff0000\"></td>"));
+}
+
+#[test]
+fn guides_thicken_borders() {
+    let mut file = get_file();
+    file.goal = None;
+    let page = HtmlRenderer::default().with_guides(1).render(&file, None);
+    assert!(page.contains("<td class=\"guide-left guide-top\"></td>"));
+}
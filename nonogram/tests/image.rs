@@ -0,0 +1,74 @@
+use nonogram as non;
+use nonogram::image::ImageRenderer;
+use nonogram::nonogram_file::{Color, ColorCode, ColorDefinition};
+
+/// A 1x3 nonogram with a filled, a not filled and an unknown tile.
+fn get_nonogram() -> non::Nonogram {
+    let mut non = non::Nonogram::new(vec![vec![1]], vec![vec![1], vec![], vec![]]);
+    non.set_tile(0, 0, non::Tile::Filled);
+    non.set_tile(0, 1, non::Tile::NotFilled);
+    non
+}
+
+fn red() -> Color {
+    Color {
+        red: 255,
+        green: 0,
+        blue: 0,
+    }
+}
+
+#[test]
+fn ppm_has_a_pixel_per_tile() {
+    let ppm = ImageRenderer::default().to_ppm(&get_nonogram());
+    let mut expected = b"P6\n3 1\n255\n".to_vec();
+    expected.extend_from_slice(&[0, 0, 0, 255, 255, 255, 192, 192, 192]);
+    assert_eq!(ppm, expected);
+}
+
+#[test]
+fn scale_repeats_pixels() {
+    let renderer = ImageRenderer::default().with_scale(2);
+    let non = get_nonogram();
+    assert_eq!(renderer.size(&non), (6, 2));
+    let pixels = renderer.pixels(&non);
+    assert_eq!(pixels.len(), 12);
+    assert_eq!(pixels[0], pixels[1]);
+    assert_eq!(pixels[0..6], pixels[6..12]);
+    assert_ne!(pixels[1], pixels[2]);
+}
+
+#[test]
+fn pbm_packs_filled_tiles_as_black_bits() {
+    let pbm = ImageRenderer::default()
+        .with_scale(3)
+        .to_pbm(&get_nonogram());
+    let mut expected = b"P4\n9 3\n".to_vec();
+    // 9 pixels take 2 bytes, with the first 3 black
+    for _ in 0..3 {
+        expected.extend_from_slice(&[0b1110_0000, 0]);
+    }
+    assert_eq!(pbm, expected);
+}
+
+#[test]
+fn colored_tiles_use_the_palette() {
+    let mut non = get_nonogram();
+    non.set_tile(0, 0, non::Tile::FilledWithColor('r'));
+    non.set_tile(0, 2, non::Tile::FilledWithColor('g'));
+    let colors = vec![ColorDefinition {
+        code: ColorCode('r'),
+        color: red(),
+    }];
+    let pixels = ImageRenderer::default().with_colors(&colors).pixels(&non);
+    assert_eq!(pixels[0], red());
+    // There is no color for 'g'
+    assert_eq!(
+        pixels[2],
+        Color {
+            red: 0,
+            green: 0,
+            blue: 0,
+        }
+    );
+}
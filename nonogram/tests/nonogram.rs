@@ -253,3 +253,223 @@ fn blank_tiles_do_not_affect_correct_solution() {
         }
     }
 }
+
+#[test]
+fn try_new_accepts_valid_clues() {
+    let non = non::Nonogram::try_new(get_small_row_clues(), get_small_column_clues());
+    assert_eq!(non, Ok(get_small_nonogram()));
+}
+
+#[test]
+fn try_new_reports_every_infeasible_line() {
+    let row_clues = vec![vec![2, 2], vec![0, 0], vec![3]];
+    let column_clues = vec![vec![3], vec![1, 1], vec![1], vec![1, 1, 1]];
+    let error = non::Nonogram::try_new(row_clues, column_clues).unwrap_err();
+    assert_eq!(
+        error.infeasible_lines,
+        vec![
+            (
+                non::Line::Row(0),
+                non::LineProblem::TooLong {
+                    required: 5,
+                    available: 4
+                }
+            ),
+            (non::Line::Row(1), non::LineProblem::ZeroLengthClue),
+            (
+                non::Line::Column(3),
+                non::LineProblem::TooLong {
+                    required: 5,
+                    available: 3
+                }
+            ),
+        ]
+    );
+    assert_eq!(error.filled_mismatch, Some((7, 9)));
+}
+
+#[test]
+fn try_new_accepts_a_lone_zero_clue() {
+    let row_clues = vec![vec![0], vec![2]];
+    let column_clues = vec![vec![1], vec![1]];
+    let non = non::Nonogram::try_new(row_clues.clone(), column_clues.clone());
+    assert_eq!(non, Ok(non::Nonogram::new(row_clues, column_clues)));
+}
+
+#[test]
+fn try_new_reports_filled_mismatch() {
+    let row_clues = vec![vec![2], vec![1, 1], vec![3]];
+    let column_clues = vec![vec![3], vec![1, 1], vec![1], vec![]];
+    let error = non::Nonogram::try_new(row_clues, column_clues).unwrap_err();
+    assert!(error.infeasible_lines.is_empty());
+    assert_eq!(error.filled_mismatch, Some((7, 6)));
+}
+
+/// A colored clue
+fn colored(length: usize, color: char) -> non::ColoredClue {
+    non::ColoredClue {
+        length,
+        color: Some(color),
+    }
+}
+
+#[test]
+fn try_colored_lets_different_colors_touch() {
+    let row_clues = vec![vec![colored(1, 'a'), colored(1, 'b')]];
+    let column_clues = vec![vec![colored(1, 'a')], vec![colored(1, 'b')]];
+    let non = non::Nonogram::try_colored(row_clues, column_clues).unwrap();
+    assert!(non.is_colored());
+    assert_eq!(non.line_clues(non::Line::Row(0)), &vec![1, 1]);
+    assert_eq!(non.line_colors(non::Line::Row(0)), &[Some('a'), Some('b')]);
+}
+
+#[test]
+fn try_colored_needs_a_gap_between_the_same_color() {
+    let row_clues = vec![vec![colored(1, 'a'), colored(1, 'a')]];
+    let column_clues = vec![vec![colored(1, 'a')], vec![colored(1, 'a')]];
+    let error = non::Nonogram::try_colored(row_clues, column_clues).unwrap_err();
+    assert_eq!(
+        error.infeasible_lines,
+        vec![(
+            non::Line::Row(0),
+            non::LineProblem::TooLong {
+                required: 3,
+                available: 2
+            }
+        )]
+    );
+}
+
+#[test]
+fn try_colored_reports_color_mismatch() {
+    let row_clues = vec![vec![colored(1, 'a'), colored(1, 'b')]];
+    let column_clues = vec![vec![colored(1, 'b')], vec![colored(1, 'b')]];
+    let error = non::Nonogram::try_colored(row_clues, column_clues).unwrap_err();
+    assert_eq!(error.filled_mismatch, None);
+    assert_eq!(error.color_mismatches, vec![('a', 1, 0), ('b', 1, 2)]);
+}
+
+#[test]
+fn colored_solution_needs_the_right_colors() {
+    let row_clues = vec![vec![colored(1, 'a'), colored(1, 'b')]];
+    let column_clues = vec![vec![colored(1, 'a')], vec![colored(1, 'b')]];
+    let mut non = non::Nonogram::colored(row_clues, column_clues);
+    non.set_tile(0, 0, non::Tile::Filled);
+    non.set_tile(0, 1, non::Tile::Filled);
+    assert!(!non.is_correct_solution());
+    assert_eq!(non.line_state(non::Line::Row(0)), non::LineState::Possible);
+    non.set_tile(0, 0, non::Tile::FilledWithColor('b'));
+    assert_eq!(
+        non.line_state(non::Line::Row(0)),
+        non::LineState::Contradicted
+    );
+    non.set_tile(0, 0, non::Tile::FilledWithColor('a'));
+    non.set_tile(0, 1, non::Tile::FilledWithColor('b'));
+    assert!(non.is_correct_solution());
+    assert_eq!(non.line_state(non::Line::Row(0)), non::LineState::Satisfied);
+}
+
+#[test]
+fn from_solution_derives_colored_clues() {
+    let a = non::Tile::FilledWithColor('a');
+    let b = non::Tile::FilledWithColor('b');
+    let non = non::Nonogram::from_solution(&[vec![a, b, b]]);
+    assert_eq!(non.line_clues(non::Line::Row(0)), &vec![1, 2]);
+    assert_eq!(non.line_colors(non::Line::Row(0)), &[Some('a'), Some('b')]);
+    assert!(non.is_correct_solution());
+}
+
+#[test]
+fn empty_lines_are_possible() {
+    let non = get_small_nonogram();
+    for line in non.lines() {
+        assert_eq!(non.line_state(line), non::LineState::Possible);
+    }
+    assert_eq!(non.contradicted_lines().count(), 0);
+}
+
+#[test]
+fn matching_row_is_satisfied_without_crossing_out() {
+    let mut non = get_small_nonogram();
+    non.set_tile(1, 0, non::Tile::Filled);
+    non.set_tile(1, 3, non::Tile::Filled);
+    assert_eq!(non.line_state(non::Line::Row(1)), non::LineState::Satisfied);
+    assert_eq!(non.line_state(non::Line::Row(0)), non::LineState::Possible);
+}
+
+#[test]
+fn impossible_lines_are_contradicted() {
+    let mut non = get_small_nonogram();
+    // Row 0 needs a single run of 2, but tiles 0 and 3 are both filled.
+    non.set_tile(0, 0, non::Tile::Filled);
+    non.set_tile(0, 3, non::Tile::Filled);
+    // Column 2 needs a single run of 1, but has two.
+    non.set_tile(0, 2, non::Tile::Filled);
+    non.set_tile(2, 2, non::Tile::Filled);
+    assert_eq!(
+        non.line_state(non::Line::Row(0)),
+        non::LineState::Contradicted
+    );
+    assert_eq!(
+        non.contradicted_lines().collect::<Vec<_>>(),
+        vec![non::Line::Row(0), non::Line::Column(2)]
+    );
+}
+
+#[test]
+fn solved_lines_are_satisfied() {
+    let non = non::Nonogram::from_solution(&get_correct_solution_grid());
+    assert!(non
+        .lines()
+        .all(|line| non.line_state(line) == non::LineState::Satisfied));
+}
+
+#[test]
+fn bounded_clue_is_placed() {
+    let mut non = non::Nonogram::new(vec![vec![3, 1]], vec![vec![]; 8]);
+    let row = non::Line::Row(0);
+    assert_eq!(non.placed_clues(row), vec![false, false]);
+    for column in 1..4 {
+        non.set_tile(0, column, non::Tile::Filled);
+    }
+    // The run is filled, but is not yet crossed out on its left.
+    assert_eq!(non.placed_clues(row), vec![false, false]);
+    non.set_tile(0, 0, non::Tile::NotFilled);
+    non.set_tile(0, 4, non::Tile::NotFilled);
+    assert_eq!(non.placed_clues(row), vec![true, false]);
+}
+
+#[test]
+fn ambiguous_run_is_not_placed() {
+    // The filled, bounded run of 1 could be either clue.
+    let mut non = non::Nonogram::new(vec![vec![1, 1, 1]], vec![vec![]; 9]);
+    non.set_tile(0, 2, non::Tile::NotFilled);
+    non.set_tile(0, 3, non::Tile::Filled);
+    non.set_tile(0, 4, non::Tile::NotFilled);
+    assert_eq!(
+        non.placed_clues(non::Line::Row(0)),
+        vec![false, false, false]
+    );
+}
+
+#[test]
+fn zero_clue_is_placed_until_a_tile_is_filled() {
+    let mut non = non::Nonogram::new(vec![vec![0]], vec![vec![], vec![]]);
+    let row = non::Line::Row(0);
+    assert_eq!(non.placed_clues(row), vec![true]);
+    non.set_tile(0, 1, non::Tile::Filled);
+    assert_eq!(non.placed_clues(row), vec![false]);
+}
+
+#[test]
+fn only_possible_clue_is_placed() {
+    // With only seven tiles, the run at 3 must be the middle clue.
+    let mut non = non::Nonogram::new(vec![vec![1, 1, 1]], vec![vec![]; 7]);
+    non.set_tile(0, 2, non::Tile::NotFilled);
+    non.set_tile(0, 3, non::Tile::Filled);
+    non.set_tile(0, 4, non::Tile::NotFilled);
+    assert_eq!(
+        non.placed_clues(non::Line::Row(0)),
+        vec![false, true, false]
+    );
+}
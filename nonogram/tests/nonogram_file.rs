@@ -0,0 +1,81 @@
+use nonogram as non;
+use nonogram::nonogram_file::{Cell, ColorCode, Hint, NonogramFile};
+
+fn hint(length: u64, color: Option<char>) -> Hint {
+    Hint {
+        length,
+        color: color.map(ColorCode),
+    }
+}
+
+/// A 1x2 file with the given hints, whose goal is `a b`.
+fn get_file(rows: Vec<Vec<Hint>>, columns: Vec<Vec<Hint>>) -> NonogramFile {
+    NonogramFile {
+        width: 2,
+        height: 1,
+        rows,
+        columns,
+        goal: Some(vec![
+            Cell::Filled(Some(ColorCode('a'))),
+            Cell::Filled(Some(ColorCode('b'))),
+        ]),
+        catalogue: None,
+        title: None,
+        by: None,
+        copyright: None,
+        license: None,
+        colors: None,
+    }
+}
+
+#[test]
+fn colored_hints_give_colored_solutions() {
+    let file = get_file(
+        vec![vec![hint(1, Some('a')), hint(1, Some('b'))]],
+        vec![vec![hint(1, Some('a'))], vec![hint(1, Some('b'))]],
+    );
+    let solutions = file.solve(2);
+    assert_eq!(solutions.len(), 1);
+    assert_eq!(
+        solutions[0].get_tile(0, 1),
+        Some(non::Tile::FilledWithColor('b'))
+    );
+}
+
+#[test]
+fn monochrome_hints_are_colored_from_the_goal() {
+    let file = get_file(
+        vec![vec![hint(2, None)]],
+        vec![vec![hint(1, None)], vec![hint(1, None)]],
+    );
+    let solutions = file.solve(2);
+    assert_eq!(solutions.len(), 1);
+    assert_eq!(
+        solutions[0].get_tile(0, 0),
+        Some(non::Tile::FilledWithColor('a'))
+    );
+    assert_eq!(
+        solutions[0].get_tile(0, 1),
+        Some(non::Tile::FilledWithColor('b'))
+    );
+}
+
+#[test]
+fn ambiguous_files_give_every_solution_asked_for() {
+    // A diagonal either way
+    let file = NonogramFile {
+        height: 2,
+        rows: vec![vec![hint(1, None)], vec![hint(1, None)]],
+        columns: vec![vec![hint(1, None)], vec![hint(1, None)]],
+        goal: None,
+        ..get_file(Vec::new(), Vec::new())
+    };
+    assert_eq!(file.solve(2).len(), 2);
+    assert_eq!(file.solve(1).len(), 1);
+}
+
+#[test]
+fn unsolvable_files_have_no_solutions() {
+    let file = get_file(vec![vec![hint(2, None)]], vec![vec![], vec![]]);
+    assert!(file.solve(2).is_empty());
+}
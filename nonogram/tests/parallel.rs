@@ -0,0 +1,45 @@
+#![cfg(feature = "rayon")]
+
+use nonogram as non;
+use nonogram::parallel;
+use nonogram::solver::{self, Status};
+use nonogram::Generator;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[test]
+fn solve_all_keeps_order() {
+    let mut rng = StdRng::seed_from_u64(33);
+    let generated: Vec<_> = (0..20)
+        .map(|_| Generator::new(10, 12).generate(&mut rng).unwrap())
+        .collect();
+    let mut puzzles: Vec<_> = generated.iter().map(|g| g.puzzle.clone()).collect();
+    let results = parallel::solve_all(&mut puzzles, solver::solve);
+    assert!(results.iter().all(|result| *result == Ok(Status::Solved)));
+    for (puzzle, generated) in puzzles.iter().zip(generated.iter()) {
+        assert_eq!(*puzzle, generated.solution);
+    }
+}
+
+#[test]
+fn parallel_solve_matches_solve() {
+    let mut rng = StdRng::seed_from_u64(33);
+    let generated = Generator::new(20, 25).generate(&mut rng).unwrap();
+    let mut puzzle = generated.puzzle.clone();
+    assert_eq!(parallel::solve(&mut puzzle), Ok(Status::Solved));
+    assert_eq!(puzzle, generated.solution);
+}
+
+#[test]
+fn parallel_solve_reports_contradictions() {
+    let mut non = non::Nonogram::new(vec![vec![2], vec![]], vec![vec![1], vec![2]]);
+    assert!(parallel::solve(&mut non).is_err());
+}
+
+#[test]
+fn parallel_solve_keeps_colors() {
+    let mut non = non::Nonogram::new(vec![vec![1]], vec![vec![1]]);
+    non.set_tile(0, 0, non::Tile::FilledWithColor('r'));
+    assert_eq!(parallel::solve(&mut non), Ok(Status::Solved));
+    assert_eq!(non.get_tile(0, 0), Some(non::Tile::FilledWithColor('r')));
+}
@@ -0,0 +1,24 @@
+#![cfg(feature = "png")]
+
+use nonogram as non;
+use nonogram::image::ImageRenderer;
+
+#[test]
+fn png_decodes_to_the_same_pixels() {
+    let mut non = non::Nonogram::new(vec![vec![1]], vec![vec![1], vec![]]);
+    non.set_tile(0, 0, non::Tile::Filled);
+    let renderer = ImageRenderer::default().with_scale(4);
+    let bytes = renderer.to_png(&non).unwrap();
+
+    let decoder = png::Decoder::new(&bytes[..]);
+    let (info, mut reader) = decoder.read_info().unwrap();
+    assert_eq!((info.width, info.height), (8, 4));
+    let mut data = vec![0; info.buffer_size()];
+    reader.next_frame(&mut data).unwrap();
+    let expected: Vec<u8> = renderer
+        .pixels(&non)
+        .iter()
+        .flat_map(|color| vec![color.red, color.green, color.blue])
+        .collect();
+    assert_eq!(data, expected);
+}
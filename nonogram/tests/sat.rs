@@ -0,0 +1,196 @@
+use nonogram as non;
+use nonogram::sat::{ColoredClue, Encoding, Literal};
+
+/// Get the sample small nonogram
+///
+/// # # _ _
+/// # _ _ #
+/// # # # _
+fn get_small_nonogram() -> non::Nonogram {
+    let row_clues = vec![vec![2], vec![1, 1], vec![3]];
+    let column_clues = vec![vec![3], vec![1, 1], vec![1], vec![1]];
+    non::Nonogram::new(row_clues, column_clues)
+}
+
+/// A tiny DPLL solver, so the encoding can be checked without a SAT solver
+/// crate. Returns the literals of a satisfying assignment.
+fn dpll(clauses: &[Vec<Literal>], assignment: &mut Vec<Literal>) -> bool {
+    let value = |assignment: &[Literal], literal: Literal| {
+        if assignment.contains(&literal) {
+            Some(true)
+        } else if assignment.contains(&-literal) {
+            Some(false)
+        } else {
+            None
+        }
+    };
+    let mut branch = None;
+    for clause in clauses {
+        if clause.iter().any(|&l| value(assignment, l) == Some(true)) {
+            continue;
+        }
+        let open: Vec<Literal> = clause
+            .iter()
+            .cloned()
+            .filter(|&l| value(assignment, l).is_none())
+            .collect();
+        match open.len() {
+            0 => return false,
+            1 => {
+                assignment.push(open[0]);
+                let len = assignment.len();
+                if dpll(clauses, assignment) {
+                    return true;
+                }
+                assignment.truncate(len - 1);
+                return false;
+            }
+            _ => branch = branch.or(Some(open[0])),
+        }
+    }
+    let literal = match branch {
+        Some(literal) => literal,
+        None => return true,
+    };
+    for &choice in &[literal, -literal] {
+        let len = assignment.len();
+        assignment.push(choice);
+        if dpll(clauses, assignment) {
+            return true;
+        }
+        assignment.truncate(len);
+    }
+    false
+}
+
+fn all_solutions(encoding: &Encoding) -> Vec<Vec<Vec<non::Tile>>> {
+    let mut clauses = encoding.clauses().to_vec();
+    let mut solutions = Vec::new();
+    loop {
+        let mut model = Vec::new();
+        if !dpll(&clauses, &mut model) {
+            return solutions;
+        }
+        let solution = encoding.decode(&model);
+        clauses.push(encoding.blocking_clause(&solution));
+        solutions.push(solution);
+    }
+}
+
+fn grid(rows: &[&str]) -> Vec<Vec<non::Tile>> {
+    rows.iter()
+        .map(|row| {
+            row.chars()
+                .map(|c| match c {
+                    '#' => non::Tile::Filled,
+                    '_' => non::Tile::NotFilled,
+                    color => non::Tile::FilledWithColor(color),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn small_nonogram_has_one_solution() {
+    let encoding = Encoding::new(&get_small_nonogram());
+    assert_eq!(
+        all_solutions(&encoding),
+        vec![grid(&["##__", "#__#", "###_"])]
+    );
+}
+
+#[test]
+fn ambiguous_puzzle_has_every_solution() {
+    let non = non::Nonogram::new(vec![vec![1], vec![1]], vec![vec![1], vec![1]]);
+    let solutions = all_solutions(&Encoding::new(&non));
+    assert_eq!(solutions.len(), 2);
+    assert!(solutions.contains(&grid(&["#_", "_#"])));
+    assert!(solutions.contains(&grid(&["_#", "#_"])));
+}
+
+#[test]
+fn known_tiles_are_kept() {
+    let mut non = non::Nonogram::new(vec![vec![1], vec![1]], vec![vec![1], vec![1]]);
+    non.set_tile(0, 0, non::Tile::NotFilled);
+    assert_eq!(
+        all_solutions(&Encoding::new(&non)),
+        vec![grid(&["_#", "#_"])]
+    );
+}
+
+#[test]
+fn unsatisfiable_clues_have_no_solutions() {
+    let non = non::Nonogram::new(vec![vec![3]], vec![vec![1], vec![1]]);
+    let encoding = Encoding::new(&non);
+    assert!(encoding.clauses().iter().any(|clause| clause.is_empty()));
+    assert!(all_solutions(&encoding).is_empty());
+}
+
+#[test]
+fn colored_blocks_may_touch() {
+    let clue = |length, color| ColoredClue {
+        length,
+        color: Some(color),
+    };
+    let row_clues = vec![vec![clue(1, 'r'), clue(1, 'b')]];
+    let column_clues = vec![vec![clue(1, 'r')], vec![clue(1, 'b')]];
+    assert_eq!(
+        all_solutions(&Encoding::colored(&row_clues, &column_clues)),
+        vec![grid(&["rb"])]
+    );
+}
+
+#[test]
+fn same_colored_blocks_need_a_gap() {
+    let clue = |length| ColoredClue {
+        length,
+        color: Some('r'),
+    };
+    let row_clues = vec![vec![clue(1), clue(1)]];
+    let column_clues = vec![vec![clue(1)], vec![clue(1)]];
+    assert!(all_solutions(&Encoding::colored(&row_clues, &column_clues)).is_empty());
+}
+
+#[test]
+fn dimacs_has_header_and_terminated_clauses() {
+    let encoding = Encoding::new(&get_small_nonogram());
+    let dimacs = encoding.to_dimacs();
+    let mut lines = dimacs.lines();
+    assert_eq!(
+        lines.next(),
+        Some(format!("p cnf {} {}", encoding.num_vars(), encoding.clauses().len()).as_str())
+    );
+    assert_eq!(lines.clone().count(), encoding.clauses().len());
+    assert!(lines.all(|line| line.ends_with('0')));
+}
+
+#[cfg(feature = "varisat")]
+#[test]
+fn varisat_finds_the_unique_solution() {
+    let non = get_small_nonogram();
+    let solutions = non::sat::solve(&non, 2);
+    assert_eq!(solutions.len(), 1);
+    assert!(solutions[0].is_correct_solution());
+}
+
+#[cfg(feature = "varisat")]
+#[test]
+fn varisat_stops_at_max_solutions() {
+    let non = non::Nonogram::new(vec![vec![1], vec![1]], vec![vec![1], vec![1]]);
+    assert_eq!(non::sat::solve(&non, 1).len(), 1);
+    assert_eq!(non::sat::solve(&non, 5).len(), 2);
+}
+
+#[cfg(feature = "varisat")]
+#[test]
+fn varisat_solves_colored_clues() {
+    let clue = |length, color| ColoredClue {
+        length,
+        color: Some(color),
+    };
+    let row_clues = vec![vec![clue(1, 'r'), clue(1, 'b')]];
+    let column_clues = vec![vec![clue(1, 'r')], vec![clue(1, 'b')]];
+    let encoding = Encoding::colored(&row_clues, &column_clues);
+    assert_eq!(encoding.solutions(2), vec![grid(&["rb"])]);
+}
@@ -0,0 +1,137 @@
+#![cfg(feature = "serde")]
+
+use nonogram as non;
+use nonogram::nonogram_file::{Cell, ColorCode, Hint, NonogramFile};
+
+/// Get the sample small nonogram
+///
+/// # # _ _
+/// # _ _ #
+/// # # # _
+fn get_small_nonogram() -> non::Nonogram {
+    let row_clues = vec![vec![2], vec![1, 1], vec![3]];
+    let column_clues = vec![vec![3], vec![1, 1], vec![1], vec![1]];
+    non::Nonogram::new(row_clues, column_clues)
+}
+
+#[test]
+fn empty_nonogram_omits_tiles() {
+    let non = get_small_nonogram();
+    let json = serde_json::to_value(&non).unwrap();
+    let expected = serde_json::json!({
+        "row_clues": [[2], [1, 1], [3]],
+        "column_clues": [[3], [1, 1], [1], [1]],
+    });
+    assert_eq!(json, expected);
+}
+
+#[test]
+fn partial_nonogram_includes_tiles() {
+    let mut non = get_small_nonogram();
+    non.set_tile(0, 0, non::Tile::Filled);
+    non.set_tile(1, 1, non::Tile::NotFilled);
+    non.set_tile(2, 2, non::Tile::FilledWithColor('r'));
+    let json = serde_json::to_value(&non).unwrap();
+    let expected = serde_json::json!({
+        "row_clues": [[2], [1, 1], [3]],
+        "column_clues": [[3], [1, 1], [1], [1]],
+        "tiles": [
+            ["filled", null, null, null],
+            [null, "not_filled", null, null],
+            [null, null, {"filled_with_color": "r"}, null],
+        ],
+    });
+    assert_eq!(json, expected);
+}
+
+#[test]
+fn nonogram_round_trips() {
+    let mut non = get_small_nonogram();
+    non.set_tile(1, 3, non::Tile::Filled);
+    non.set_tile(2, 3, non::Tile::NotFilled);
+    let json = serde_json::to_string(&non).unwrap();
+    let parsed: non::Nonogram = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, non);
+}
+
+#[test]
+fn lone_zero_clue_round_trips() {
+    let non = non::Nonogram::new(vec![vec![0], vec![1]], vec![vec![1]]);
+    let json = serde_json::to_string(&non).unwrap();
+    let parsed: non::Nonogram = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, non);
+}
+
+#[test]
+fn colored_nonogram_round_trips() {
+    let clue = |length, color| non::ColoredClue {
+        length,
+        color: Some(color),
+    };
+    let row_clues = vec![vec![clue(1, 'a'), clue(1, 'b')]];
+    let column_clues = vec![vec![clue(1, 'a')], vec![clue(1, 'b')]];
+    let non = non::Nonogram::colored(row_clues, column_clues);
+    let json = serde_json::to_value(&non).unwrap();
+    assert_eq!(json["row_colors"], serde_json::json!([["a", "b"]]));
+    let parsed: non::Nonogram = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed, non);
+}
+
+#[test]
+fn colors_must_match_the_clues() {
+    let json = r#"{"row_clues": [[1]], "column_clues": [[1]], "row_colors": [[]]}"#;
+    assert!(serde_json::from_str::<non::Nonogram>(json).is_err());
+}
+
+#[test]
+fn invalid_clues_are_rejected() {
+    let json = r#"{"row_clues": [[5]], "column_clues": [[1], [1]]}"#;
+    assert!(serde_json::from_str::<non::Nonogram>(json).is_err());
+}
+
+#[test]
+fn wrong_size_tiles_are_rejected() {
+    let json = r#"{
+        "row_clues": [[1]],
+        "column_clues": [[1], []],
+        "tiles": [["filled"]]
+    }"#;
+    assert!(serde_json::from_str::<non::Nonogram>(json).is_err());
+}
+
+#[test]
+fn nonogram_file_round_trips() {
+    let file = NonogramFile {
+        width: 2,
+        height: 1,
+        rows: vec![vec![Hint {
+            length: 2,
+            color: None,
+        }]],
+        columns: vec![
+            vec![Hint {
+                length: 1,
+                color: Some(ColorCode('a')),
+            }],
+            vec![Hint {
+                length: 1,
+                color: None,
+            }],
+        ],
+        goal: Some(vec![Cell::Filled(Some(ColorCode('a'))), Cell::Filled(None)]),
+        catalogue: None,
+        title: Some("Tiny".to_string()),
+        by: None,
+        copyright: None,
+        license: None,
+        colors: None,
+    };
+    let json = serde_json::to_string(&file).unwrap();
+    let parsed: NonogramFile = serde_json::from_str(&json).unwrap();
+    assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+    assert_eq!(parsed.title, Some("Tiny".to_string()));
+    assert_eq!(
+        parsed.columns[0][0].color.as_ref().map(|code| code.0),
+        Some('a')
+    );
+}
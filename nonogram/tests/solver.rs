@@ -0,0 +1,233 @@
+use nonogram as non;
+use nonogram::solver::{self, Contradiction, Status};
+
+const F: Option<non::Tile> = Some(non::Tile::Filled);
+const N: Option<non::Tile> = Some(non::Tile::NotFilled);
+const U: Option<non::Tile> = None;
+
+/// Get the sample small nonogram
+///
+/// # # _ _
+/// # _ _ #
+/// # # # _
+fn get_small_nonogram() -> non::Nonogram {
+    let row_clues = vec![vec![2], vec![1, 1], vec![3]];
+    let column_clues = vec![vec![3], vec![1, 1], vec![1], vec![1]];
+    non::Nonogram::new(row_clues, column_clues)
+}
+
+#[test]
+fn overlapping_tiles_are_filled() {
+    assert_eq!(
+        solver::solve_line(&[4], &[U, U, U, U, U]),
+        Some(vec![U, F, F, F, U])
+    );
+}
+
+#[test]
+fn full_line_is_solved() {
+    assert_eq!(
+        solver::solve_line(&[2, 2], &[U, U, U, U, U]),
+        Some(vec![F, F, N, F, F])
+    );
+}
+
+#[test]
+fn empty_clues_cross_out_line() {
+    assert_eq!(solver::solve_line(&[], &[U, U, U]), Some(vec![N, N, N]));
+    assert_eq!(solver::solve_line(&[0], &[U, U, U]), Some(vec![N, N, N]));
+}
+
+#[test]
+fn known_tiles_are_extended_from_edge() {
+    assert_eq!(
+        solver::solve_line(&[3], &[F, U, U, U, U, U]),
+        Some(vec![F, F, F, N, N, N])
+    );
+}
+
+#[test]
+fn gaps_too_small_are_crossed_out() {
+    assert_eq!(
+        solver::solve_line(&[2], &[U, N, U, U, N, U]),
+        Some(vec![N, N, F, F, N, N])
+    );
+}
+
+#[test]
+fn contradicting_line_is_none() {
+    assert_eq!(solver::solve_line(&[2], &[F, N, F]), None);
+    assert_eq!(solver::solve_line(&[1, 1], &[U, U]), None);
+}
+
+#[test]
+fn solves_small_nonogram() {
+    let mut non = get_small_nonogram();
+    assert_eq!(solver::solve(&mut non), Ok(Status::Solved));
+    assert!(non.is_correct_solution());
+    assert_eq!(
+        non.get_row(1).cloned().collect::<Vec<_>>(),
+        vec![F, N, N, F]
+    );
+}
+
+#[test]
+fn ambiguous_nonogram_stalls() {
+    let mut non = non::Nonogram::new(vec![vec![1], vec![1]], vec![vec![1], vec![1]]);
+    assert_eq!(solver::solve(&mut non), Ok(Status::Stalled));
+    assert!(!non.is_complete());
+}
+
+#[test]
+fn contradicting_nonogram_is_an_error() {
+    let mut non = non::Nonogram::new(vec![vec![2], vec![]], vec![vec![1], vec![2]]);
+    assert_eq!(
+        solver::solve(&mut non),
+        Err(Contradiction {
+            line: non::Line::Column(1)
+        })
+    );
+}
+
+#[test]
+fn search_finds_every_solution() {
+    let non = non::Nonogram::new(vec![vec![1], vec![1]], vec![vec![1], vec![1]]);
+    let search = solver::search(&non, 10);
+    assert_eq!(search.solutions.len(), 2);
+    assert!(search
+        .solutions
+        .iter()
+        .all(non::Nonogram::is_correct_solution));
+    assert_eq!(search.max_depth, 1);
+}
+
+#[test]
+fn search_stops_at_max_solutions() {
+    let non = non::Nonogram::new(vec![vec![1], vec![1]], vec![vec![1], vec![1]]);
+    assert_eq!(solver::search(&non, 1).solutions.len(), 1);
+}
+
+#[test]
+fn effort_counts_sweeps() {
+    let mut non = get_small_nonogram();
+    let (status, effort) = solver::solve_with_effort(&mut non).unwrap();
+    assert_eq!(status, Status::Solved);
+    assert!(effort.sweeps >= 1);
+    assert!(effort.lines_checked >= non.num_rows() + non.num_cols());
+}
+
+/// Get a nonogram that line logic cannot start, but probing can solve
+///
+/// # _ # _
+/// _ # _ #
+/// _ _ _ #
+/// _ # # _
+fn get_probing_nonogram() -> non::Nonogram {
+    let row_clues = vec![vec![1, 1], vec![1, 1], vec![1], vec![2]];
+    let column_clues = vec![vec![1], vec![1, 1], vec![1, 1], vec![2]];
+    non::Nonogram::new(row_clues, column_clues)
+}
+
+#[test]
+fn probing_solves_what_line_logic_cannot() {
+    let mut non = get_probing_nonogram();
+    assert_eq!(solver::solve(&mut non), Ok(Status::Stalled));
+
+    let (status, effort) = solver::solve_with_probing(&mut non).unwrap();
+    assert_eq!(status, Status::Solved);
+    assert!(effort.probes > 0);
+    assert!(non.is_correct_solution());
+    assert_eq!(
+        non.get_row(0).cloned().collect::<Vec<_>>(),
+        vec![F, N, F, N]
+    );
+}
+
+#[test]
+fn probing_stalls_on_ambiguous_nonogram() {
+    let mut non = non::Nonogram::new(vec![vec![1], vec![1]], vec![vec![1], vec![1]]);
+    let (status, _) = solver::solve_with_probing(&mut non).unwrap();
+    assert_eq!(status, Status::Stalled);
+    assert!(!non.is_complete());
+}
+
+#[test]
+fn probing_finds_contradictions() {
+    let mut non = non::Nonogram::new(vec![vec![1], vec![1]], vec![vec![2], vec![1]]);
+    assert!(solver::solve_with_probing(&mut non).is_err());
+}
+
+#[test]
+fn different_colors_may_touch() {
+    let a = Some(non::Tile::FilledWithColor('a'));
+    let b = Some(non::Tile::FilledWithColor('b'));
+    let colors = [Some('a'), Some('b')];
+    assert_eq!(
+        solver::solve_colored_line(&[1, 1], &colors, &[U, U]),
+        Some(vec![a, b])
+    );
+    assert_eq!(solver::solve_colored_line(&[1, 1], &colors, &[b, U]), None);
+}
+
+#[test]
+fn filled_tiles_are_given_their_color() {
+    let a = Some(non::Tile::FilledWithColor('a'));
+    let b = Some(non::Tile::FilledWithColor('b'));
+    let colors = [Some('a'), Some('b')];
+    assert_eq!(
+        solver::solve_colored_line(&[2, 1], &colors, &[F, U, U, F]),
+        Some(vec![a, a, N, b])
+    );
+    assert_eq!(
+        solver::solve_colored_line(&[2, 2], &colors, &[U, F, F, U]),
+        Some(vec![a, a, b, b])
+    );
+    // Either block could cover the filled tile, so its color is not known.
+    assert_eq!(
+        solver::solve_colored_line(&[1, 1], &colors, &[U, F, U]),
+        Some(vec![U, F, U])
+    );
+}
+
+/// Get a colored nonogram
+///
+/// a b
+/// b _
+fn get_colored_nonogram() -> non::Nonogram {
+    let clue = |length, color| non::ColoredClue {
+        length,
+        color: Some(color),
+    };
+    let row_clues = vec![vec![clue(1, 'a'), clue(1, 'b')], vec![clue(1, 'b')]];
+    let column_clues = vec![vec![clue(1, 'a'), clue(1, 'b')], vec![clue(1, 'b')]];
+    non::Nonogram::colored(row_clues, column_clues)
+}
+
+#[test]
+fn solves_colored_nonogram() {
+    let mut non = get_colored_nonogram();
+    assert_eq!(solver::solve(&mut non), Ok(Status::Solved));
+    assert!(non.is_correct_solution());
+    assert_eq!(
+        non.get_row(1).cloned().collect::<Vec<_>>(),
+        vec![Some(non::Tile::FilledWithColor('b')), N]
+    );
+}
+
+#[test]
+fn search_colors_its_guesses() {
+    let clue = |color| non::ColoredClue {
+        length: 1,
+        color: Some(color),
+    };
+    // Like two tiles on a diagonal, in either of two ways
+    let row_clues = vec![vec![clue('a')], vec![clue('a')]];
+    let column_clues = vec![vec![clue('a')], vec![clue('a')]];
+    let non = non::Nonogram::colored(row_clues, column_clues);
+    let search = solver::search(&non, 10);
+    assert_eq!(search.solutions.len(), 2);
+    assert!(search
+        .solutions
+        .iter()
+        .all(non::Nonogram::is_correct_solution));
+}
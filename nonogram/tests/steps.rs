@@ -0,0 +1,151 @@
+use nonogram as non;
+use nonogram::difficulty::Category;
+use nonogram::solver::Status;
+use nonogram::steps::{self, Step, Technique};
+use nonogram::Generator;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// Get the sample small nonogram
+///
+/// # # _ _
+/// # _ _ #
+/// # # # _
+fn get_small_nonogram() -> non::Nonogram {
+    let row_clues = vec![vec![2], vec![1, 1], vec![3]];
+    let column_clues = vec![vec![3], vec![1, 1], vec![1], vec![1]];
+    non::Nonogram::new(row_clues, column_clues)
+}
+
+#[test]
+fn steps_solve_small_nonogram() {
+    let non = get_small_nonogram();
+    let mut steps = steps::steps(&non);
+    let all_steps: Vec<Step> = (&mut steps).collect();
+    assert!(!all_steps.is_empty());
+    assert_eq!(steps.result(), Some(Ok(Status::Solved)));
+    assert!(steps.nonogram().is_correct_solution());
+
+    let num_changes: usize = all_steps.iter().map(|step| step.changes.len()).sum();
+    assert_eq!(num_changes, non.num_rows() * non.num_cols());
+}
+
+#[test]
+fn first_step_is_overlap() {
+    let non = get_small_nonogram();
+    let first = steps::steps(&non).next().unwrap();
+    // The "3" in the bottom row covers the middle two tiles wherever it goes.
+    assert_eq!(
+        first,
+        Step {
+            line: non::Line::Row(2),
+            clue: Some(0),
+            technique: Technique::Overlap,
+            changes: vec![((2, 1), non::Tile::Filled), ((2, 2), non::Tile::Filled)],
+            contradiction: None,
+        }
+    );
+}
+
+#[test]
+fn gap_too_small_is_detected() {
+    let mut non = non::Nonogram::new(vec![vec![2]], vec![vec![], vec![], vec![1], vec![1]]);
+    non.set_tile(0, 1, non::Tile::NotFilled);
+    let first = steps::steps(&non).next().unwrap();
+    assert_eq!(first.line, non::Line::Row(0));
+    assert_eq!(first.technique, Technique::GapTooSmall);
+    assert_eq!(first.changes, vec![((0, 0), non::Tile::NotFilled)]);
+    // The 2 reaches the gap at the edge but can't fit in it
+    assert_eq!(first.clue, Some(0));
+}
+
+#[test]
+fn stalled_puzzle_reports_stalled() {
+    let non = non::Nonogram::new(vec![vec![1], vec![1]], vec![vec![1], vec![1]]);
+    let mut steps = steps::steps(&non);
+    assert_eq!(steps.next(), None);
+    assert_eq!(steps.result(), Some(Ok(Status::Stalled)));
+}
+
+#[test]
+fn walkthrough_ends_with_summary() {
+    let lines = steps::walkthrough(&get_small_nonogram(), &non::Formatter::default());
+    assert!(lines[0].starts_with("Step 1: row 2, clue 0 (overlap): fill (2, 1) fill (2, 2)"));
+    assert_eq!(lines.last().unwrap(), "Solved.");
+}
+
+#[test]
+fn probing_steps_continue_after_line_logic_stalls() {
+    let row_clues = vec![vec![1, 1], vec![1, 1], vec![1], vec![2]];
+    let column_clues = vec![vec![1], vec![1, 1], vec![1, 1], vec![2]];
+    let non = non::Nonogram::new(row_clues, column_clues);
+    assert_eq!(steps::steps(&non).count(), 0);
+
+    let mut steps = steps::steps(&non).with_probing();
+    let all_steps: Vec<Step> = (&mut steps).collect();
+    assert_eq!(all_steps[0].technique, Technique::Probe);
+    assert_eq!(steps.result(), Some(Ok(Status::Solved)));
+    assert!(steps.nonogram().is_correct_solution());
+}
+
+#[test]
+fn probe_step_is_on_a_line_through_the_probed_tile() {
+    let row_clues = vec![vec![1, 1], vec![1, 1], vec![1], vec![2]];
+    let column_clues = vec![vec![1], vec![1, 1], vec![1, 1], vec![2]];
+    let non = non::Nonogram::new(row_clues, column_clues);
+    let probes = steps::steps(&non)
+        .with_probing()
+        .filter(|step| step.technique == Technique::Probe);
+    for step in probes {
+        let is_on_line = |&((row, column), _): &((usize, usize), non::Tile)| match step.line {
+            non::Line::Row(index) => row == index,
+            non::Line::Column(index) => column == index,
+        };
+        assert!(step.changes.iter().any(is_on_line), "{}", step);
+        if let Some(line) = step.contradiction {
+            assert!(step.to_string().contains(&format!("in {}", line)));
+        }
+    }
+}
+
+#[test]
+fn steps_solve_colored_nonogram() {
+    let clue = |length, color| non::ColoredClue {
+        length,
+        color: Some(color),
+    };
+    let row_clues = vec![vec![clue(1, 'a'), clue(1, 'b')]];
+    let column_clues = vec![vec![clue(1, 'a')], vec![clue(1, 'b')]];
+    let non = non::Nonogram::colored(row_clues, column_clues);
+    let mut steps = steps::steps(&non);
+    let descriptions: Vec<String> = (&mut steps).map(|step| step.to_string()).collect();
+    assert_eq!(
+        descriptions,
+        vec![
+            "row 0, clue 0 (overlap): fill (0, 0) with a",
+            "row 0, clue 1 (overlap): fill (0, 1) with b",
+        ]
+    );
+    assert_eq!(steps.result(), Some(Ok(Status::Solved)));
+    assert!(steps.nonogram().is_correct_solution());
+}
+
+#[test]
+fn every_line_step_names_its_clue() {
+    let mut rng = StdRng::seed_from_u64(30);
+    for _ in 0..20 {
+        let generated = Generator::new(8, 8)
+            .max_difficulty(Category::Hard)
+            .generate(&mut rng)
+            .unwrap();
+        let non = &generated.puzzle;
+        for step in steps::steps(non).with_probing() {
+            let clues = non.line_clues(step.line);
+            if step.technique == Technique::Probe || clues.is_empty() {
+                continue;
+            }
+            let clue = step.clue.unwrap_or_else(|| panic!("no clue for {}", step));
+            assert!(clue < clues.len());
+        }
+    }
+}